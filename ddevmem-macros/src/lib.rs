@@ -1,8 +1,9 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 use syn::{
     braced,
+    ext::IdentExt,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
     Attribute, Expr, Ident, Result, Token, Type, Visibility,
@@ -28,6 +29,10 @@ struct RegisterEntry {
     /// When set, `ty` is the *element* type and the entry generates an
     /// indexed API (`name(idx)`, `set_name(idx, v)`, …).
     array_len: Option<Expr>,
+    /// Declared reset value (`= expr` after the type); `0` when absent.
+    reset: Option<Expr>,
+    /// `#[allow(overlap)]`: may share addresses with other registers.
+    allow_overlap: bool,
     bitfields: Vec<Bitfield>,
 }
 
@@ -36,28 +41,81 @@ enum AccessKind {
     Rw,
     Ro,
     Wo,
+    /// Write 1 to clear.
+    W1c,
+    /// Write 1 to set.
+    W1s,
+    /// Read to clear.
+    Rc,
+    /// Write 1 to trigger; the bit clears itself.
+    Wosc,
 }
 
 struct Bitfield {
     attrs: Vec<Attribute>,
+    /// Access kind overriding the register's (`w1c rx: 1`), with its span.
+    access: Option<(AccessKind, proc_macro2::Span)>,
+    /// `#[allow(overlap)]`: may share bits with other fields.
+    allow_overlap: bool,
     name: Ident,
     lo: Expr,
     hi: Expr,
     field_type: FieldType,
+    /// Conversion to a physical value, if declared.
+    scale: Option<Scale>,
 }
 
 enum FieldType {
     Raw,
     Bool,
     Cast(Type),
+    /// Signed integer type: two's complement, sign-extended from the
+    /// field's top bit.
+    Signed(Type),
     Enum(EnumDef),
 }
 
+/// Linear conversion between a field's integer value and a physical one,
+/// `value = raw × lsb + offset`: `scale 2.5 offset 0 unit "mV"`, or
+/// `as fixed(Q4.8)`.
+struct Scale {
+    /// Physical value of one step.
+    lsb: Expr,
+    offset: Option<Expr>,
+    unit: Option<syn::LitStr>,
+    /// Fixed-point format, if declared with `as fixed(..)`.
+    fixed: Option<Fixed>,
+}
+
+/// `Qm.n` (two's complement, `m` counting the sign bit) or `UQm.n`
+/// (unsigned): `m` integer and `n` fractional bits.
+struct Fixed {
+    signed: bool,
+    int_bits: u32,
+    frac_bits: u32,
+    span: proc_macro2::Span,
+}
+
 struct EnumDef {
     name: Ident,
+    decode: EnumDecode,
     variants: Vec<EnumVariant>,
 }
 
+/// What the getter of an enum field returns for a raw value no variant
+/// declares: `as enum(result) Name { .. }` and so on.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EnumDecode {
+    /// The first declared variant (the default).
+    First,
+    /// `Result<Name, T>`, with the raw value as the error.
+    Result,
+    /// `Option<Name>`.
+    Option,
+    /// An added `Unknown(T)` variant.
+    Unknown,
+}
+
 struct EnumVariant {
     name: Ident,
     value: Expr,
@@ -65,6 +123,33 @@ struct EnumVariant {
 
 // ─── Parse ───────────────────────────────────────────────────────────────────
 
+/// Removes `overlap` from the `#[allow(...)]` attributes in `attrs`, which
+/// would otherwise be forwarded to the generated methods as an unknown
+/// lint. Returns whether it was present.
+fn take_allow_overlap(attrs: &mut Vec<Attribute>) -> Result<bool> {
+    let mut found = false;
+    let mut kept = Vec::with_capacity(attrs.len());
+    for attr in attrs.drain(..) {
+        if !attr.path().is_ident("allow") {
+            kept.push(attr);
+            continue;
+        }
+        let lints = attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)?;
+        let (overlap, rest): (Vec<_>, Vec<_>) =
+            lints.into_iter().partition(|lint| lint.is_ident("overlap"));
+        if overlap.is_empty() {
+            kept.push(attr);
+            continue;
+        }
+        found = true;
+        if !rest.is_empty() {
+            kept.push(syn::parse_quote!(#[allow(#(#rest),*)]));
+        }
+    }
+    *attrs = kept;
+    Ok(found)
+}
+
 /// Parse a bit-position expression: a literal integer or a parenthesized
 /// expression. This avoids syn's greedy `Expr::parse` consuming `0..=2` as
 /// a range.
@@ -128,20 +213,11 @@ impl Parse for RegisterEntry {
     fn parse(input: ParseStream) -> Result<Self> {
         let offset: Expr = input.parse()?;
         input.parse::<Token![=>]>()?;
-        let attrs = input.call(Attribute::parse_outer)?;
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let allow_overlap = take_allow_overlap(&mut attrs)?;
 
         let kind_ident: Ident = input.parse()?;
-        let kind = match kind_ident.to_string().as_str() {
-            "rw" => AccessKind::Rw,
-            "ro" => AccessKind::Ro,
-            "wo" => AccessKind::Wo,
-            _ => {
-                return Err(syn::Error::new(
-                    kind_ident.span(),
-                    "expected `rw`, `ro`, or `wo`",
-                ))
-            }
-        };
+        let kind = AccessKind::parse_ident(&kind_ident)?;
 
         let name: Ident = input.parse()?;
         input.parse::<Token![:]>()?;
@@ -154,10 +230,17 @@ impl Parse for RegisterEntry {
             other => (other, None),
         };
 
+        let reset = if input.peek(Token![=]) && !input.peek(Token![=>]) {
+            input.parse::<Token![=]>()?;
+            Some(Expr::parse_without_eager_brace(input)?)
+        } else {
+            None
+        };
+
         let bitfields = if input.peek(syn::token::Brace) {
             let content;
             braced!(content in input);
-            let mut fields = Vec::new();
+            let mut fields: Vec<Bitfield> = Vec::new();
             while !content.is_empty() {
                 fields.push(content.parse()?);
                 if content.is_empty() {
@@ -171,6 +254,19 @@ impl Parse for RegisterEntry {
             Vec::new()
         };
 
+        // Only `rw` registers may mix access kinds; elsewhere a field
+        // override could contradict what the register allows.
+        for bf in &bitfields {
+            if let Some((access, span)) = bf.access {
+                if kind != AccessKind::Rw && access != kind {
+                    return Err(syn::Error::new(
+                        span,
+                        "a field's access kind can only differ from that of an `rw` register",
+                    ));
+                }
+            }
+        }
+
         Ok(RegisterEntry {
             offset,
             attrs,
@@ -178,6 +274,8 @@ impl Parse for RegisterEntry {
             name,
             ty,
             array_len,
+            reset,
+            allow_overlap,
             bitfields,
         })
     }
@@ -185,7 +283,14 @@ impl Parse for RegisterEntry {
 
 impl Parse for Bitfield {
     fn parse(input: ParseStream) -> Result<Self> {
-        let attrs = input.call(Attribute::parse_outer)?;
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let allow_overlap = take_allow_overlap(&mut attrs)?;
+        let access = if input.peek(Ident) && input.peek2(Ident) {
+            let kind_ident: Ident = input.parse()?;
+            Some((AccessKind::parse_ident(&kind_ident)?, kind_ident.span()))
+        } else {
+            None
+        };
         let name: Ident = input.parse()?;
         input.parse::<Token![:]>()?;
 
@@ -206,18 +311,55 @@ impl Parse for Bitfield {
         };
 
         // Check for `as ...`
+        let mut fixed = None;
         let field_type = if input.peek(Token![as]) {
             input.parse::<Token![as]>()?;
 
-            if input.peek(Token![enum]) {
+            if input.peek(Ident)
+                && input.peek2(syn::token::Paren)
+                && input.fork().parse::<Ident>()? == "fixed"
+            {
+                let _: Ident = input.parse()?;
+                let content;
+                syn::parenthesized!(content in input);
+                fixed = Some(content.parse::<Fixed>()?);
+                FieldType::Raw
+            } else if input.peek(Token![enum]) {
                 input.parse::<Token![enum]>()?;
+                let decode = if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    let mode: Ident = content.parse()?;
+                    match mode.to_string().as_str() {
+                        "result" => EnumDecode::Result,
+                        "option" => EnumDecode::Option,
+                        "unknown" => EnumDecode::Unknown,
+                        _ => {
+                            return Err(syn::Error::new(
+                                mode.span(),
+                                "expected `result`, `option`, or `unknown`",
+                            ))
+                        }
+                    }
+                } else {
+                    EnumDecode::First
+                };
                 let enum_name: Ident = input.parse()?;
                 let content;
                 braced!(content in input);
                 let variants: Punctuated<EnumVariant, Token![,]> =
                     content.parse_terminated(EnumVariant::parse, Token![,])?;
+                if decode == EnumDecode::Unknown {
+                    if let Some(v) = variants.iter().find(|v| v.name == "Unknown") {
+                        return Err(syn::Error::new(
+                            v.name.span(),
+                            "`enum(unknown)` adds an `Unknown` variant itself",
+                        ));
+                    }
+                }
                 FieldType::Enum(EnumDef {
                     name: enum_name,
+                    decode,
                     variants: variants.into_iter().collect(),
                 })
             } else if input.peek(Ident) {
@@ -225,6 +367,12 @@ impl Parse for Bitfield {
                 if ident == "bool" {
                     let _: Ident = input.parse()?;
                     FieldType::Bool
+                } else if matches!(
+                    ident.to_string().as_str(),
+                    "i8" | "i16" | "i32" | "i64" | "i128" | "isize"
+                ) {
+                    let ty: Type = input.parse()?;
+                    FieldType::Signed(ty)
                 } else {
                     let ty: Type = input.parse()?;
                     FieldType::Cast(ty)
@@ -236,13 +384,121 @@ impl Parse for Bitfield {
         } else {
             FieldType::Raw
         };
+        let scale = Scale::parse_clauses(input, fixed, &field_type)?;
 
         Ok(Bitfield {
             attrs,
+            access,
+            allow_overlap,
             name,
             lo,
             hi,
             field_type,
+            scale,
+        })
+    }
+}
+
+impl Scale {
+    /// Parses the optional `scale`, `offset` and `unit` clauses after a
+    /// field's type, in that order.
+    fn parse_clauses(
+        input: ParseStream,
+        fixed: Option<Fixed>,
+        field_type: &FieldType,
+    ) -> Result<Option<Self>> {
+        let clause = |input: ParseStream, name: &str| -> Result<Option<Ident>> {
+            if input.peek(Ident) && input.fork().parse::<Ident>()? == name {
+                input.parse().map(Some)
+            } else {
+                Ok(None)
+            }
+        };
+        let scale_kw = clause(input, "scale")?;
+        let lsb = match (&scale_kw, &fixed) {
+            (Some(kw), Some(_)) => {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "`fixed(..)` already sets the scale",
+                ))
+            }
+            (Some(_), None) => Some(Expr::parse_without_eager_brace(input)?),
+            (None, Some(fixed)) => {
+                let lsb = 2f64.powi(-(fixed.frac_bits as i32));
+                let lit = syn::LitFloat::new(&format!("{lsb:?}f64"), fixed.span);
+                Some(syn::parse_quote!(#lit))
+            }
+            (None, None) => None,
+        };
+        let offset = match clause(input, "offset")? {
+            Some(kw) if lsb.is_none() => {
+                return Err(syn::Error::new(kw.span(), "`offset` needs a `scale`"))
+            }
+            Some(kw) if fixed.is_some() => {
+                return Err(syn::Error::new(
+                    kw.span(),
+                    "`fixed(..)` fields have no offset",
+                ))
+            }
+            Some(_) => Some(Expr::parse_without_eager_brace(input)?),
+            None => None,
+        };
+        let unit = match clause(input, "unit")? {
+            Some(kw) if lsb.is_none() => {
+                return Err(syn::Error::new(kw.span(), "`unit` needs a `scale`"))
+            }
+            Some(_) => Some(input.parse::<syn::LitStr>()?),
+            None => None,
+        };
+        let Some(lsb) = lsb else { return Ok(None) };
+        if let (Some(kw), FieldType::Bool | FieldType::Enum(_)) = (&scale_kw, field_type) {
+            return Err(syn::Error::new(
+                kw.span(),
+                "only integer fields can be scaled",
+            ));
+        }
+        Ok(Some(Scale {
+            lsb,
+            offset,
+            unit,
+            fixed,
+        }))
+    }
+}
+
+impl Parse for Fixed {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let format: Ident = input.parse()?;
+        input.parse::<Token![.]>()?;
+        let frac: syn::LitInt = input.parse()?;
+        let span = format.span().join(frac.span()).unwrap_or(format.span());
+        let error = || {
+            syn::Error::new(
+                span,
+                "expected a fixed-point format such as `Q4.8` or `UQ0.16`",
+            )
+        };
+        let format_str = format.to_string();
+        let (signed, int_bits) = match format_str.strip_prefix("UQ") {
+            Some(m) => (false, m),
+            None => (true, format_str.strip_prefix('Q').ok_or_else(error)?),
+        };
+        let int_bits: u32 = int_bits.parse().map_err(|_| error())?;
+        let frac_bits: u32 = frac.base10_parse().map_err(|_| error())?;
+        if signed && int_bits == 0 {
+            return Err(syn::Error::new(
+                span,
+                "`Qm.n` counts the sign bit in `m`, so `m` is at least 1",
+            ));
+        }
+        if int_bits + frac_bits > 128 {
+            return Err(error());
+        }
+        Ok(Fixed {
+            signed,
+            int_bits,
+            frac_bits,
+            span,
         })
     }
 }
@@ -259,25 +515,282 @@ impl Parse for EnumVariant {
 // ─── Code generation ─────────────────────────────────────────────────────────
 
 impl AccessKind {
+    fn parse_ident(ident: &Ident) -> Result<Self> {
+        Ok(match ident.to_string().as_str() {
+            "rw" => AccessKind::Rw,
+            "ro" => AccessKind::Ro,
+            "wo" => AccessKind::Wo,
+            "w1c" => AccessKind::W1c,
+            "w1s" => AccessKind::W1s,
+            "rc" => AccessKind::Rc,
+            "wosc" => AccessKind::Wosc,
+            _ => {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "expected `rw`, `ro`, `wo`, `w1c`, `w1s`, `rc`, or `wosc`",
+                ))
+            }
+        })
+    }
     fn has_read(self) -> bool {
-        matches!(self, AccessKind::Rw | AccessKind::Ro)
+        matches!(
+            self,
+            AccessKind::Rw | AccessKind::Ro | AccessKind::W1c | AccessKind::W1s | AccessKind::Rc
+        )
     }
     fn has_write(self) -> bool {
-        matches!(self, AccessKind::Rw | AccessKind::Wo)
+        matches!(
+            self,
+            AccessKind::Rw | AccessKind::Wo | AccessKind::W1c | AccessKind::W1s | AccessKind::Wosc
+        )
     }
     fn has_modify(self) -> bool {
         self == AccessKind::Rw
     }
+    /// Writing a 0 bit has no effect, so writes carry only the target bits
+    /// and never read the register first.
+    fn is_one_shot(self) -> bool {
+        matches!(self, AccessKind::W1c | AccessKind::W1s | AccessKind::Wosc)
+    }
+    /// Whether `reset_<reg>()` makes sense: writing the reset value
+    /// restores it.
+    fn has_reset(self) -> bool {
+        matches!(self, AccessKind::Rw | AccessKind::Wo)
+    }
+    /// Doc line appended to generated accessors describing the semantics.
+    fn note(self) -> Option<&'static str> {
+        match self {
+            AccessKind::W1c => Some("Write 1 to clear: 1 bits clear, 0 bits are left alone."),
+            AccessKind::W1s => Some("Write 1 to set: 1 bits set, 0 bits are left alone."),
+            AccessKind::Wosc => {
+                Some("Write 1 to trigger: the bit clears itself, 0 bits have no effect.")
+            }
+            AccessKind::Rc => Some("**Side effect:** reading clears it."),
+            AccessKind::Rw | AccessKind::Ro | AccessKind::Wo => None,
+        }
+    }
     #[cfg_attr(not(feature = "web"), allow(dead_code))]
     fn as_str(self) -> &'static str {
         match self {
             AccessKind::Rw => "rw",
             AccessKind::Ro => "ro",
             AccessKind::Wo => "wo",
+            AccessKind::W1c => "w1c",
+            AccessKind::W1s => "w1s",
+            AccessKind::Rc => "rc",
+            AccessKind::Wosc => "wosc",
         }
     }
 }
 
+impl EnumDef {
+    /// Getter return type and expression decoding `raw`, a field value of
+    /// type `ty`.
+    fn decode(&self, ty: &Type, raw: TokenStream2) -> (TokenStream2, TokenStream2) {
+        let ename = &self.name;
+        let try_from = quote! { <#ename as ::core::convert::TryFrom<#ty>>::try_from(#raw) };
+        match self.decode {
+            EnumDecode::First | EnumDecode::Unknown => {
+                (quote! { #ename }, quote! { #ename::from_raw(#raw) })
+            }
+            EnumDecode::Result => (quote! { ::core::result::Result<#ename, #ty> }, try_from),
+            EnumDecode::Option => (
+                quote! { ::core::option::Option<#ename> },
+                quote! { #try_from.ok() },
+            ),
+        }
+    }
+
+    #[cfg_attr(not(feature = "web"), allow(dead_code))]
+    fn decode_str(&self) -> &'static str {
+        match self.decode {
+            EnumDecode::First => "first",
+            EnumDecode::Result => "result",
+            EnumDecode::Option => "option",
+            EnumDecode::Unknown => "unknown",
+        }
+    }
+}
+
+impl Bitfield {
+    /// The field's own access kind, or the register's.
+    fn kind(&self, entry: &RegisterEntry) -> AccessKind {
+        self.access.map_or(entry.kind, |(kind, _)| kind)
+    }
+}
+
+/// Sign-extends `raw`, a `width`-bit two's-complement field value, into
+/// `signed_ty`.
+fn sign_extend(raw: TokenStream2, width: TokenStream2, signed_ty: &Type) -> TokenStream2 {
+    quote! {{
+        let sign = 1i128 << ((#width) - 1);
+        (((#raw as i128) ^ sign).wrapping_sub(sign)) as #signed_ty
+    }}
+}
+
+/// Whether `value` fits a `width`-bit two's-complement field.
+fn signed_fits(value: TokenStream2, width: TokenStream2) -> TokenStream2 {
+    quote! {{
+        let half = 1i128 << ((#width) - 1);
+        (-half..half).contains(&(#value as i128))
+    }}
+}
+
+/// Asserts that `value` fits a `width`-bit two's-complement field, then
+/// converts it to the register type `ty` (masked by the caller).
+fn signed_to_bits(
+    name: String,
+    value: TokenStream2,
+    width: TokenStream2,
+    ty: &Type,
+) -> TokenStream2 {
+    let msg = format!("value out of range for signed bitfield `{name}`");
+    let fits = signed_fits(value.clone(), width);
+    quote! {{
+        assert!(#fits, #msg);
+        #value as #ty
+    }}
+}
+
+/// `#[doc]` lines for the panicking setter of a signed field, pointing at
+/// its `try_` twin.
+fn signed_setter_doc(try_fn: &Ident) -> TokenStream2 {
+    let panics = format!(
+        "Panics if `value` does not fit the field's two's-complement range; \
+         [`{try_fn}`](Self::{try_fn}) returns it instead."
+    );
+    quote! {
+        #[doc = ""]
+        #[doc = "# Panics"]
+        #[doc = ""]
+        #[doc = #panics]
+    }
+}
+
+impl Scale {
+    /// Whether the field's integer value is two's complement.
+    fn signed(&self, field_type: &FieldType) -> bool {
+        self.fixed
+            .as_ref()
+            .map_or(matches!(field_type, FieldType::Signed(_)), |f| f.signed)
+    }
+
+    fn offset(&self) -> TokenStream2 {
+        match &self.offset {
+            Some(offset) => quote! { (#offset) as f64 },
+            None => quote! { 0.0 },
+        }
+    }
+
+    /// Physical value of `raw`, the `width`-bit field bits.
+    fn to_physical(&self, raw: TokenStream2, width: TokenStream2, signed: bool) -> TokenStream2 {
+        let steps = if signed {
+            sign_extend(raw, width, &syn::parse_quote!(i128))
+        } else {
+            quote! { (#raw as i128) }
+        };
+        let lsb = &self.lsb;
+        let offset = self.offset();
+        quote! { (#steps as f64) * ((#lsb) as f64) + #offset }
+    }
+
+    /// Field bits for the physical `value`, rounded to the nearest step and
+    /// saturated to the field's range (NaN gives 0), as register type `ty`
+    /// (masked by the caller).
+    fn to_bits(
+        &self,
+        value: TokenStream2,
+        width: TokenStream2,
+        signed: bool,
+        ty: &Type,
+    ) -> TokenStream2 {
+        let lsb = &self.lsb;
+        let offset = self.offset();
+        let range = if signed {
+            quote! {
+                let max = i128::MAX >> (128 - width);
+                let min = -max - 1;
+            }
+        } else {
+            quote! {
+                let max = (u128::MAX >> (128 - width)).min(i128::MAX as u128) as i128;
+                let min = 0;
+            }
+        };
+        quote! {{
+            let width: u32 = #width;
+            #range
+            let steps = ((#value - #offset) / ((#lsb) as f64)).round() as i128;
+            steps.clamp(min, max) as #ty
+        }}
+    }
+
+    /// `#[doc]` lines for the physical accessors of `field`.
+    fn doc(&self, field: &str, setter: bool) -> TokenStream2 {
+        let unit = self
+            .unit
+            .as_ref()
+            .map(|u| format!(" in {}", u.value()))
+            .unwrap_or_default();
+        let conversion = match &self.fixed {
+            Some(fixed) => format!("{} fixed point", fixed.format_str()),
+            None => {
+                let lsb = &self.lsb;
+                let mut conv = format!("raw × {}", quote!(#lsb));
+                if let Some(offset) = &self.offset {
+                    conv += &format!(" + {}", quote!(#offset));
+                }
+                conv
+            }
+        };
+        let first = format!("`{field}`{unit}: {conversion}.");
+        let rounding = setter.then(|| {
+            let note = "Rounds to the nearest step and saturates at the field's range.";
+            quote! { #[doc = ""] #[doc = #note] }
+        });
+        quote! { #[doc = #first] #rounding }
+    }
+}
+
+impl Fixed {
+    fn width(&self) -> u32 {
+        self.int_bits + self.frac_bits
+    }
+
+    fn format_str(&self) -> String {
+        let prefix = if self.signed { "Q" } else { "UQ" };
+        format!("{prefix}{}.{}", self.int_bits, self.frac_bits)
+    }
+}
+
+/// `#[doc]` attributes describing `kind`, appended after the user's docs.
+fn kind_doc(kind: AccessKind) -> TokenStream2 {
+    match kind.note() {
+        Some(note) => quote! { #[doc = ""] #[doc = #note] },
+        None => TokenStream2::new(),
+    }
+}
+
+/// Mask of the write-one fields of an `rw` register, which must be written
+/// as 0 when other fields are read-modify-written; `None` if there are none.
+fn one_shot_mask(map_mod: &Ident, entry: &RegisterEntry) -> Option<TokenStream2> {
+    if entry.kind != AccessKind::Rw {
+        return None;
+    }
+    let reg = entry.name.unraw();
+    let masks: Vec<_> = entry
+        .bitfields
+        .iter()
+        .filter(|bf| bf.kind(entry).is_one_shot())
+        .map(|bf| format_ident!("{}_MASK", bf.name.unraw().to_string().to_uppercase()))
+        .collect();
+    if masks.is_empty() {
+        None
+    } else {
+        Some(quote! { (#(#map_mod::#reg::#masks)|*) })
+    }
+}
+
 #[cfg_attr(not(feature = "web"), allow(dead_code))]
 fn extract_doc_string(attrs: &[Attribute]) -> String {
     let mut doc = String::new();
@@ -294,94 +807,1041 @@ fn extract_doc_string(attrs: &[Attribute]) -> String {
                     if !doc.is_empty() {
                         doc.push('\n');
                     }
-                    doc.push_str(trimmed);
+                    doc.push_str(trimmed);
+                }
+            }
+        }
+    }
+    doc
+}
+
+/// Enum definitions, placed in the map module and re-exported next to the
+/// map by [`gen_enum_reexports`].
+fn gen_enum_defs(entries: &[RegisterEntry]) -> TokenStream2 {
+    let mut tokens = TokenStream2::new();
+    for entry in entries {
+        let ty = &entry.ty;
+        for bf in &entry.bitfields {
+            if let FieldType::Enum(enum_def) = &bf.field_type {
+                let ename = &enum_def.name;
+                let variant_names: Vec<_> = enum_def.variants.iter().map(|v| &v.name).collect();
+                let variant_values: Vec<_> = enum_def.variants.iter().map(|v| &v.value).collect();
+
+                let unknown = enum_def.decode == EnumDecode::Unknown;
+                let unknown_variant = unknown.then(|| {
+                    quote! {
+                        /// A raw value no declared variant matches.
+                        Unknown(#ty),
+                    }
+                });
+                let unknown_raw = unknown.then(|| quote! { Self::Unknown(raw) => raw, });
+
+                let from_raw = match enum_def.decode {
+                    EnumDecode::First => quote! {
+                        /// Convert from a raw register value.
+                        ///
+                        /// Unknown values map to the first declared variant.
+                        #[inline]
+                        #[allow(unreachable_patterns)]
+                        pub fn from_raw(v: #ty) -> Self {
+                            match v {
+                                #(#variant_values => Self::#variant_names,)*
+                                _ => {
+                                    let _variants = [#(Self::#variant_names,)*];
+                                    _variants[0]
+                                }
+                            }
+                        }
+                    },
+                    EnumDecode::Unknown => quote! {
+                        /// Convert from a raw register value.
+                        ///
+                        /// Unknown values map to [`Unknown`](Self::Unknown).
+                        #[inline]
+                        #[allow(unreachable_patterns)]
+                        pub fn from_raw(v: #ty) -> Self {
+                            match v {
+                                #(#variant_values => Self::#variant_names,)*
+                                _ => Self::Unknown(v),
+                            }
+                        }
+                    },
+                    EnumDecode::Result | EnumDecode::Option => TokenStream2::new(),
+                };
+
+                // `unknown` enums decode every value, so they convert with
+                // `From`, which also provides an infallible `TryFrom`.
+                let conversion = if unknown {
+                    quote! {
+                        impl ::core::convert::From<#ty> for #ename {
+                            fn from(v: #ty) -> Self {
+                                Self::from_raw(v)
+                            }
+                        }
+                    }
+                } else {
+                    quote! {
+                        impl ::core::convert::TryFrom<#ty> for #ename {
+                            /// The raw value, which no variant declares.
+                            type Error = #ty;
+
+                            #[allow(unreachable_patterns)]
+                            fn try_from(v: #ty) -> ::core::result::Result<Self, #ty> {
+                                match v {
+                                    #(#variant_values => Ok(Self::#variant_names),)*
+                                    _ => Err(v),
+                                }
+                            }
+                        }
+                    }
+                };
+
+                tokens.extend(quote! {
+                    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                    pub enum #ename {
+                        #(#variant_names,)*
+                        #unknown_variant
+                    }
+
+                    impl #ename {
+                        #from_raw
+
+                        /// Convert to raw register value.
+                        #[inline]
+                        pub fn to_raw(self) -> #ty {
+                            match self {
+                                #(Self::#variant_names => #variant_values as #ty,)*
+                                #unknown_raw
+                            }
+                        }
+                    }
+
+                    #conversion
+
+                    impl ::core::fmt::Display for #ename {
+                        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                            ::core::fmt::Debug::fmt(self, f)
+                        }
+                    }
+                });
+            }
+        }
+    }
+    tokens
+}
+
+fn gen_enum_reexports(
+    vis: &Visibility,
+    map_mod: &Ident,
+    entries: &[RegisterEntry],
+) -> TokenStream2 {
+    let mut tokens = TokenStream2::new();
+    for bf in entries.iter().flat_map(|e| &e.bitfields) {
+        if let FieldType::Enum(enum_def) = &bf.field_type {
+            let ename = &enum_def.name;
+            tokens.extend(quote! { #vis use #map_mod::#ename; });
+        }
+    }
+    tokens
+}
+
+fn gen_bounds_checks(bus: &Type, entries: &[RegisterEntry]) -> TokenStream2 {
+    let mut checks = TokenStream2::new();
+    for entry in entries {
+        let name = &entry.name;
+        let offset = &entry.offset;
+        let ty = &entry.ty;
+        let count_expr = match &entry.array_len {
+            Some(n) => quote! { (#n) },
+            None => quote! { 1usize },
+        };
+        checks.extend(quote! {
+            const _: () = assert!(
+                ::core::mem::size_of::<#ty>() <= ::core::mem::size_of::<#bus>(),
+                "register type must not be wider than bus type"
+            );
+            const _: () = assert!(
+                (#offset) % ::core::mem::align_of::<#bus>() == 0,
+                "register offset must be aligned to bus width"
+            );
+            {
+                let size = (#count_expr) * ::core::mem::size_of::<#bus>();
+                if (#offset) + size > devmem.len() {
+                    return Err(::ddevmem::Error::RegisterOutOfBounds {
+                        register: stringify!(#name),
+                        offset: (#offset),
+                        size,
+                        len: devmem.len(),
+                    });
+                }
+            }
+        });
+    }
+    checks
+}
+
+/// Value of an integer literal, possibly parenthesized, or of `a + b` /
+/// `a - b` over such literals (exclusive ranges become `(hi) - 1`). `None`
+/// for anything else, which is left to the const assertions.
+fn literal_value(expr: &Expr) -> Option<i128> {
+    match expr {
+        Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(int),
+            ..
+        }) => int.base10_parse().ok(),
+        Expr::Paren(e) => literal_value(&e.expr),
+        Expr::Group(e) => literal_value(&e.expr),
+        Expr::Binary(e) => {
+            let (left, right) = (literal_value(&e.left)?, literal_value(&e.right)?);
+            match e.op {
+                syn::BinOp::Add(_) => left.checked_add(right),
+                syn::BinOp::Sub(_) => left.checked_sub(right),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Width of a primitive integer type; `None` for `usize`, aliases and
+/// anything else whose width the macro cannot know.
+fn type_bits(ty: &Type) -> Option<i128> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    match path.path.get_ident()?.to_string().as_str() {
+        "u8" | "i8" => Some(8),
+        "u16" | "i16" => Some(16),
+        "u32" | "i32" => Some(32),
+        "u64" | "i64" => Some(64),
+        "u128" | "i128" => Some(128),
+        _ => None,
+    }
+}
+
+/// Checks the layout wherever offsets and bit positions are literals, so
+/// mistakes are reported at the offending entry before any code is
+/// generated. Other expressions are covered by [`gen_layout_checks`].
+fn validate(map: &RegisterMap) -> Result<()> {
+    let mut errors = Vec::new();
+    let bus_bytes = type_bits(&map.bus).map(|bits| bits / 8);
+    let mut placed: Vec<(&RegisterEntry, i128, i128)> = Vec::new();
+    for entry in &map.entries {
+        validate_bitfields(entry, &mut errors);
+
+        let count = match &entry.array_len {
+            Some(n) => literal_value(n),
+            None => Some(1),
+        };
+        let (Some(start), Some(count), Some(bytes)) =
+            (literal_value(&entry.offset), count, bus_bytes)
+        else {
+            continue;
+        };
+        let end = start + count * bytes;
+        if !entry.allow_overlap {
+            let clash = placed.iter().find(|(other, o_start, o_end)| {
+                !other.allow_overlap && start < *o_end && *o_start < end
+            });
+            if let Some((other, o_start, o_end)) = clash {
+                errors.push(syn::Error::new(
+                    entry.name.span(),
+                    format!(
+                        "register `{}` at {start:#X}..{end:#X} overlaps `{}` at {o_start:#X}..{o_end:#X}; \
+                         add `#[allow(overlap)]` if the alias is intended",
+                        entry.name, other.name
+                    ),
+                ));
+            }
+        }
+        placed.push((entry, start, end));
+    }
+    match errors.into_iter().reduce(|mut all, e| {
+        all.combine(e);
+        all
+    }) {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+fn validate_bitfields(entry: &RegisterEntry, errors: &mut Vec<syn::Error>) {
+    let bits = type_bits(&entry.ty);
+    let mut placed: Vec<(&Bitfield, i128, i128)> = Vec::new();
+    for bf in &entry.bitfields {
+        let (Some(lo), Some(hi)) = (literal_value(&bf.lo), literal_value(&bf.hi)) else {
+            continue;
+        };
+        let name = &bf.name;
+        let error = |msg: String| syn::Error::new(name.span(), msg);
+        if lo > hi {
+            errors.push(error(format!(
+                "bitfield `{name}` is empty: low bit {lo} is above high bit {hi}"
+            )));
+            continue;
+        }
+        if let Some(bits) = bits.filter(|&bits| hi >= bits) {
+            errors.push(error(format!(
+                "bitfield `{name}` (bits {lo}..={hi}) does not fit the {bits}-bit register `{}`",
+                entry.name
+            )));
+            continue;
+        }
+        if let FieldType::Signed(signed_ty) = &bf.field_type {
+            let width = hi - lo + 1;
+            if let Some(bits) = type_bits(signed_ty).filter(|&bits| bits < width) {
+                errors.push(syn::Error::new_spanned(
+                    signed_ty,
+                    format!(
+                        "`{}` has {bits} bits, too few for the {width}-bit field `{name}`",
+                        quote!(#signed_ty)
+                    ),
+                ));
+            }
+        }
+        if let Some(fixed) = bf.scale.as_ref().and_then(|scale| scale.fixed.as_ref()) {
+            let width = hi - lo + 1;
+            if i128::from(fixed.width()) != width {
+                errors.push(syn::Error::new(
+                    fixed.span,
+                    format!(
+                        "`{}` needs a {}-bit field, but `{name}` has {width}",
+                        fixed.format_str(),
+                        fixed.width()
+                    ),
+                ));
+            }
+        }
+        if !bf.allow_overlap {
+            let clash = placed
+                .iter()
+                .find(|(other, o_lo, o_hi)| !other.allow_overlap && lo <= *o_hi && *o_lo <= hi);
+            if let Some((other, o_lo, o_hi)) = clash {
+                errors.push(error(format!(
+                    "bitfield `{name}` (bits {lo}..={hi}) overlaps `{}` (bits {o_lo}..={o_hi}); \
+                     add `#[allow(overlap)]` if the alias is intended",
+                    other.name
+                )));
+            }
+        }
+        placed.push((bf, lo, hi));
+    }
+}
+
+/// Const assertions on the layout, placed next to the map struct: the checks
+/// of [`validate`] it could not evaluate because an offset or bit position is
+/// not a literal. Each is spanned at the offending entry.
+fn gen_layout_checks(name: &Ident, bus: &Type, entries: &[RegisterEntry]) -> TokenStream2 {
+    let bus_known = type_bits(bus).is_some();
+    let placed = |entry: &RegisterEntry| {
+        bus_known
+            && literal_value(&entry.offset).is_some()
+            && entry
+                .array_len
+                .as_ref()
+                .is_none_or(|n| literal_value(n).is_some())
+    };
+    let known = |expr: &Expr| literal_value(expr).is_some();
+    // The conditions are constant by design.
+    let allow = quote! {
+        #[allow(
+            unused_comparisons,
+            unused_assignments,
+            clippy::assertions_on_constants,
+            clippy::eq_op,
+            clippy::absurd_extreme_comparisons
+        )]
+    };
+    let assoc = |entry: &RegisterEntry, suffix: &str| {
+        format_ident!("{}_{suffix}", entry.name.unraw().to_string().to_uppercase())
+    };
+    let size = |entry: &RegisterEntry| {
+        let len = assoc(entry, "LEN");
+        match entry.array_len {
+            Some(_) => quote! { (#name::#len * ::core::mem::size_of::<#bus>()) },
+            None => quote! { ::core::mem::size_of::<#bus>() },
+        }
+    };
+    let mut checks = TokenStream2::new();
+
+    // Registers the macro could not place itself are checked together with
+    // all the others in one const block: sorted by offset, registers are
+    // disjoint exactly when each ends before the next one starts.
+    let checked: Vec<&RegisterEntry> = entries.iter().filter(|e| !e.allow_overlap).collect();
+    if checked.len() > 1 && !checked.iter().all(|e| placed(e)) {
+        let n = checked.len();
+        let ranges = checked.iter().enumerate().map(|(k, entry)| {
+            let (start, size) = (assoc(entry, "OFFSET"), size(entry));
+            quote! { (#name::#start, #name::#start + #size, #k) }
+        });
+        let clashes = checked.iter().enumerate().map(|(k, entry)| {
+            let msg = format!(
+                "register `{}` overlaps the register before it in address order",
+                entry.name.unraw()
+            );
+            quote_spanned! {entry.name.span()=> #k => panic!(#msg), }
+        });
+        checks.extend(quote! {
+            #allow
+            const _: () = {
+                let mut regs: [(usize, usize, usize); #n] = [#(#ranges),*];
+                // Insertion sort: linear for maps declared in offset order.
+                let mut i = 1;
+                while i < #n {
+                    let mut j = i;
+                    while j > 0 && regs[j - 1].0 > regs[j].0 {
+                        let swapped = regs[j - 1];
+                        regs[j - 1] = regs[j];
+                        regs[j] = swapped;
+                        j -= 1;
+                    }
+                    i += 1;
+                }
+                let mut i = 1;
+                while i < #n {
+                    if regs[i].0 < regs[i - 1].1 {
+                        match regs[i].2 {
+                            #(#clashes)*
+                            _ => {}
+                        }
+                    }
+                    i += 1;
+                }
+            };
+        });
+    }
+
+    for entry in entries {
+        let reg = entry.name.unraw();
+        let ty = &entry.ty;
+        for bf in &entry.bitfields {
+            let field = bf.name.unraw();
+            let span = bf.name.span();
+            let (lo, hi) = (&bf.lo, &bf.hi);
+            let bits_known = known(lo) && known(hi);
+            if !bits_known {
+                let empty = format!("bitfield `{reg}.{field}` is empty: low bit is above high bit");
+                checks.extend(quote_spanned! {span=>
+                    #allow
+                    const _: () = assert!((#lo) <= (#hi), #empty);
+                });
+            }
+            if !bits_known || type_bits(ty).is_none() {
+                let too_wide = format!("bitfield `{reg}.{field}` does not fit the register");
+                checks.extend(quote_spanned! {span=>
+                    #allow
+                    const _: () = assert!((#hi) < <#ty>::BITS, #too_wide);
+                });
+            }
+            if let FieldType::Signed(signed_ty) = &bf.field_type {
+                if !bits_known || type_bits(signed_ty).is_none() {
+                    let msg = format!(
+                        "`{}` is too narrow for bitfield `{reg}.{field}`",
+                        quote!(#signed_ty)
+                    );
+                    checks.extend(quote_spanned! {span=>
+                        #allow
+                        const _: () = assert!((#hi) - (#lo) + 1 <= <#signed_ty>::BITS, #msg);
+                    });
+                }
+            }
+            let fixed = bf.scale.as_ref().and_then(|scale| scale.fixed.as_ref());
+            if let Some(fixed) = fixed.filter(|_| !bits_known) {
+                let bits = fixed.width();
+                let msg = format!(
+                    "bitfield `{reg}.{field}` is not {bits} bits wide, as `{}` needs",
+                    fixed.format_str()
+                );
+                checks.extend(quote_spanned! {span=>
+                    #allow
+                    const _: () = assert!((#hi) - (#lo) + 1 == #bits, #msg);
+                });
+            }
+        }
+
+        // Likewise one block per register for its bitfields, accumulating
+        // the bits taken so far. Empty or too wide fields are skipped here;
+        // the checks above report them.
+        let fields: Vec<&Bitfield> = entry
+            .bitfields
+            .iter()
+            .filter(|b| !b.allow_overlap)
+            .collect();
+        if fields.len() > 1 && !fields.iter().all(|b| known(&b.lo) && known(&b.hi)) {
+            let steps = fields.iter().map(|bf| {
+                let (lo, hi) = (&bf.lo, &bf.hi);
+                let msg = format!(
+                    "bitfield `{reg}.{}` overlaps an earlier bitfield",
+                    bf.name.unraw()
+                );
+                quote_spanned! {bf.name.span()=>
+                    let (lo, hi): (u32, u32) = (#lo, #hi);
+                    if lo <= hi && hi < 128 {
+                        let mask = (u128::MAX >> (127 - (hi - lo))) << lo;
+                        if taken & mask != 0 {
+                            panic!(#msg);
+                        }
+                        taken |= mask;
+                    }
+                }
+            });
+            checks.extend(quote! {
+                #allow
+                const _: () = {
+                    let mut taken: u128 = 0;
+                    #(#steps)*
+                };
+            });
+        }
+    }
+    checks
+}
+
+/// Seeds declared reset values through `DevMem::preset_unchecked` in `new()`;
+/// only the emulator stores them. Must follow the bounds checks.
+fn gen_presets(bus: &Type, map_mod: &Ident, entries: &[RegisterEntry]) -> TokenStream2 {
+    let mut presets = TokenStream2::new();
+    for entry in entries.iter().filter(|e| e.reset.is_some()) {
+        let reg = entry.name.unraw();
+        let preset = |offset: TokenStream2| {
+            quote! {
+                unsafe { devmem.preset_unchecked::<#bus>(#offset, #map_mod::#reg::RESET as #bus) }
+            }
+        };
+        presets.extend(match &entry.array_len {
+            Some(n) => {
+                let each = preset(
+                    quote! { #map_mod::#reg::OFFSET + idx * ::core::mem::size_of::<#bus>() },
+                );
+                quote! {
+                    for idx in 0..(#n) {
+                        #each
+                    }
+                }
+            }
+            None => preset(quote! { #map_mod::#reg::OFFSET }),
+        });
+    }
+    presets
+}
+
+/// `reset_all()` and `is_reset()`, covering every `rw` register.
+fn gen_reset_all(
+    vis: &Visibility,
+    bus: &Type,
+    map_mod: &Ident,
+    entries: &[RegisterEntry],
+) -> TokenStream2 {
+    let mut resets = TokenStream2::new();
+    let mut checks = TokenStream2::new();
+    for entry in entries.iter().filter(|e| e.kind == AccessKind::Rw) {
+        let reg = entry.name.unraw();
+        let reset_fn = format_ident!("reset_{}", entry.name);
+        resets.extend(quote! { self.#reset_fn(); });
+        let ty = &entry.ty;
+        let check = |offset: TokenStream2| {
+            quote! {
+                let value = unsafe { self.devmem.read_unchecked::<#bus>(#offset) } as #ty;
+                if value != #map_mod::#reg::RESET {
+                    return false;
+                }
+            }
+        };
+        checks.extend(match &entry.array_len {
+            Some(n) => {
+                let each =
+                    check(quote! { #map_mod::#reg::OFFSET + idx * ::core::mem::size_of::<#bus>() });
+                quote! {
+                    for idx in 0..(#n) {
+                        #each
+                    }
+                }
+            }
+            None => check(quote! { #map_mod::#reg::OFFSET }),
+        });
+    }
+    quote! {
+        /// Writes the reset value to every `rw` register.
+        #vis fn reset_all(&mut self) {
+            #resets
+        }
+
+        /// Whether every `rw` register holds its reset value.
+        #vis fn is_reset(&self) -> bool {
+            #checks
+            true
+        }
+    }
+}
+
+/// Expression for the number of bytes spanned by all registers, i.e. the
+/// length `claim()` reserves starting at offset 0.
+fn gen_extent(bus: &Type, entries: &[RegisterEntry]) -> TokenStream2 {
+    let mut ends = TokenStream2::new();
+    for entry in entries {
+        let offset = &entry.offset;
+        let count_expr = match &entry.array_len {
+            Some(n) => quote! { (#n) },
+            None => quote! { 1usize },
+        };
+        ends.extend(quote! {
+            let end = (#offset) + (#count_expr) * ::core::mem::size_of::<#bus>();
+            if end > extent {
+                extent = end;
+            }
+        });
+    }
+    quote! {
+        {
+            let mut extent = 0usize;
+            #ends
+            extent
+        }
+    }
+}
+
+/// `TimerRegs` → `timer_regs`.
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Module named after the map in snake case.
+fn map_mod_ident(map: &RegisterMap) -> Ident {
+    format_ident!("{}", to_snake_case(&map.name.unraw().to_string()))
+}
+
+/// `rx_fifo` → `RxFifoValue`.
+fn value_type_ident(reg: &Ident) -> Ident {
+    let mut camel = String::new();
+    for part in reg.unraw().to_string().split('_').filter(|p| !p.is_empty()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    format_ident!("{}Value", camel)
+}
+
+/// Associated consts on the map struct, plus the map module: one submodule
+/// of constants per register and the register value types.
+///
+/// Offsets, lengths and bit positions are evaluated in the associated consts,
+/// where the map is declared. The submodules repeat literal values and
+/// re-export everything else from the map struct, so expressions naming
+/// items of the declaring scope are never re-evaluated inside them.
+fn gen_map_module(vis: &Visibility, map: &RegisterMap) -> (TokenStream2, TokenStream2) {
+    let name = &map.name;
+    let map_mod = map_mod_ident(map);
+    let enum_defs = gen_enum_defs(&map.entries);
+    let mut assoc = TokenStream2::new();
+    let mut reg_mods = TokenStream2::new();
+
+    for entry in &map.entries {
+        let reg = entry.name.unraw();
+        let upper = reg.to_string().to_uppercase();
+        let ty = &entry.ty;
+        let offset = &entry.offset;
+        // The literal itself, or the associated const `assoc` holding it.
+        let literal_or = |exprs: &[&Expr], value: TokenStream2, assoc: &Ident| {
+            if exprs.iter().all(|e| literal_value(e).is_some()) {
+                value
+            } else {
+                quote! { super::super::#name::#assoc }
+            }
+        };
+
+        let offset_const = format_ident!("{}_OFFSET", upper);
+        let offset_doc = format!("Offset of `{reg}` (element 0 for arrays).");
+        let reset_const = format_ident!("{}_RESET", upper);
+        let reset_doc = format!("Reset value of `{reg}` (every element for arrays).");
+        let reset = match &entry.reset {
+            Some(value) => quote! { #value },
+            None => quote! { 0 },
+        };
+        let reset_exprs: Vec<&Expr> = entry.reset.iter().collect();
+        assoc.extend(quote! {
+            #[doc = #offset_doc]
+            #vis const #offset_const: usize = #offset;
+            #[doc = #reset_doc]
+            #vis const #reset_const: #ty = #reset;
+        });
+        let offset_item = literal_or(&[offset], quote! { #offset }, &offset_const);
+        let reset_item = literal_or(&reset_exprs, reset, &reset_const);
+        let mut items = quote! {
+            #[doc = #offset_doc]
+            pub const OFFSET: usize = #offset_item;
+            #[doc = #reset_doc]
+            pub const RESET: #ty = #reset_item;
+        };
+
+        if let Some(n) = &entry.array_len {
+            let len_const = format_ident!("{}_LEN", upper);
+            let len_doc = format!("Number of elements of `{reg}`.");
+            assoc.extend(quote! {
+                #[doc = #len_doc]
+                #vis const #len_const: usize = #n;
+            });
+            let len_item = literal_or(&[n], quote! { #n }, &len_const);
+            items.extend(quote! {
+                #[doc = #len_doc]
+                pub const LEN: usize = #len_item;
+            });
+        }
+
+        for bf in &entry.bitfields {
+            let field = bf.name.unraw();
+            let field_upper = field.to_string().to_uppercase();
+            let lo = &bf.lo;
+            let hi = &bf.hi;
+            let mask = format_ident!("{}_MASK", field_upper);
+            let shift = format_ident!("{}_SHIFT", field_upper);
+            let mask_doc = format!("Mask of `{reg}.{field}`, in place.");
+            let shift_doc = format!("Bit position of `{reg}.{field}`.");
+            let mask_value = quote! {{
+                // Guarded so a bad range fails only the layout checks.
+                let width: u32 = if (#hi) >= (#lo) { (#hi) - (#lo) + 1 } else { 0 };
+                let mask: #ty = if width >= <#ty>::BITS { <#ty>::MAX } else { (1 << width) - 1 };
+                if (#lo) < <#ty>::BITS { mask << (#lo) } else { 0 }
+            }};
+
+            let assoc_mask = format_ident!("{}_{}_MASK", upper, field_upper);
+            let assoc_shift = format_ident!("{}_{}_SHIFT", upper, field_upper);
+            assoc.extend(quote! {
+                #[doc = #mask_doc]
+                #vis const #assoc_mask: #ty = #mask_value;
+                #[doc = #shift_doc]
+                #vis const #assoc_shift: u32 = #lo;
+            });
+            let mask_item = literal_or(&[lo, hi], mask_value, &assoc_mask);
+            let shift_item = literal_or(&[lo], quote! { #lo }, &assoc_shift);
+            items.extend(quote! {
+                #[doc = #mask_doc]
+                pub const #mask: #ty = #mask_item;
+                #[doc = #shift_doc]
+                pub const #shift: u32 = #shift_item;
+            });
+        }
+
+        // Only the register type can name an item of the declaring scope.
+        let import = type_bits(ty).is_none().then(|| {
+            quote! {
+                #[allow(unused_imports)]
+                use super::super::*;
+            }
+        });
+        let reg_doc = format!("Constants of register `{reg}`.");
+        reg_mods.extend(quote! {
+            #[doc = #reg_doc]
+            pub mod #reg {
+                #import
+                #items
+            }
+        });
+        reg_mods.extend(gen_value_type(entry));
+    }
+
+    let map_doc = format!("Register constants and value types of [`{}`].", map.name);
+    let module = quote! {
+        #[doc = #map_doc]
+        #[allow(dead_code)]
+        #vis mod #map_mod {
+            #[allow(unused_imports)]
+            use super::*;
+            #enum_defs
+            #reg_mods
+        }
+    };
+    (assoc, module)
+}
+
+/// Field of the map struct holding the last value written to a `wo`
+/// register, so its bitfield setters never read the register back.
+fn shadow_ident(entry: &RegisterEntry) -> Ident {
+    format_ident!("shadow_{}", entry.name.unraw())
+}
+
+/// Place expression for the shadow of a `wo` register (element `idx` for
+/// arrays); `None` for readable registers.
+fn shadow_place(entry: &RegisterEntry) -> Option<TokenStream2> {
+    if entry.kind != AccessKind::Wo {
+        return None;
+    }
+    let shadow = shadow_ident(entry);
+    Some(match entry.array_len {
+        Some(_) => quote! { self.#shadow[idx] },
+        None => quote! { self.#shadow },
+    })
+}
+
+/// Runs `action` on the shadow of the `wo` register `entry` if the raw
+/// byte offset `offset` (in scope in the generated code) addresses it.
+#[cfg(feature = "web")]
+fn shadow_at_offset(
+    bus: &Type,
+    entry: &RegisterEntry,
+    action: impl Fn(TokenStream2) -> TokenStream2,
+) -> TokenStream2 {
+    let shadow = shadow_ident(entry);
+    let offset = &entry.offset;
+    match &entry.array_len {
+        None => {
+            let action = action(quote! { self.#shadow });
+            quote! {
+                if offset == (#offset) {
+                    #action
+                }
+            }
+        }
+        Some(n) => {
+            let action = action(quote! { self.#shadow[idx] });
+            quote! {
+                let size = ::core::mem::size_of::<#bus>();
+                if let Some(idx) = offset
+                    .checked_sub(#offset)
+                    .filter(|rel| rel.is_multiple_of(size) && rel / size < (#n))
+                    .map(|rel| rel / size)
+                {
+                    #action
+                }
+            }
+        }
+    }
+}
+
+/// Shadow fields of the map struct and their initializers in `new()`, which
+/// start at the reset value.
+fn gen_shadows(map_mod: &Ident, entries: &[RegisterEntry]) -> (TokenStream2, TokenStream2) {
+    let mut fields = TokenStream2::new();
+    let mut inits = TokenStream2::new();
+    for entry in entries.iter().filter(|e| e.kind == AccessKind::Wo) {
+        let shadow = shadow_ident(entry);
+        let ty = &entry.ty;
+        let reg = entry.name.unraw();
+        let reset = quote! { #map_mod::#reg::RESET };
+        let (field_ty, init) = match &entry.array_len {
+            Some(n) => (quote! { [#ty; #n] }, quote! { [#reset; #n] }),
+            None => (quote! { #ty }, reset),
+        };
+        fields.extend(quote! {
+            /// Last value written, used instead of reading the register back.
+            #shadow: #field_ty,
+        });
+        inits.extend(quote! { #shadow: #init, });
+    }
+    (fields, inits)
+}
+
+/// Copyable value of a register with bitfields: typed getters and
+/// builder-style `with_*` setters over the raw bits, so several fields can
+/// be changed with one bus write. Registers without bitfields get none.
+fn gen_value_type(entry: &RegisterEntry) -> TokenStream2 {
+    if entry.bitfields.is_empty() {
+        return TokenStream2::new();
+    }
+    let reg = entry.name.unraw();
+    let ty = &entry.ty;
+    let value_ty = value_type_ident(&entry.name);
+
+    let mut accessors = TokenStream2::new();
+    let mut debug_fields = TokenStream2::new();
+    for bf in &entry.bitfields {
+        let field = bf.name.unraw();
+        let field_upper = field.to_string().to_uppercase();
+        let mask = format_ident!("{}_MASK", field_upper);
+        let shift = format_ident!("{}_SHIFT", field_upper);
+        let with_fn = format_ident!("with_{}", field);
+        let attrs = &bf.attrs;
+        let raw = quote! { ((self.0 & #reg::#mask) >> #reg::#shift) };
+        let raw_bits = raw.clone();
+
+        let (field_ty, get, to_bits) = match &bf.field_type {
+            FieldType::Raw => (quote! { #ty }, raw, quote! { value }),
+            FieldType::Bool => (
+                quote! { bool },
+                quote! { #raw != 0 },
+                quote! { value as #ty },
+            ),
+            FieldType::Cast(cast_ty) => (
+                quote! { #cast_ty },
+                quote! { #raw as #cast_ty },
+                quote! { value as #ty },
+            ),
+            FieldType::Signed(signed_ty) => {
+                let width = quote! { #reg::#mask.count_ones() };
+                (
+                    quote! { #signed_ty },
+                    sign_extend(raw, width.clone(), signed_ty),
+                    signed_to_bits(format!("{reg}.{field}"), quote! { value }, width, ty),
+                )
+            }
+            FieldType::Enum(enum_def) => {
+                let ename = &enum_def.name;
+                (quote! { #ename }, raw, quote! { value.to_raw() })
+            }
+        };
+        // Fallible enums return a `Result` / `Option` but are set by value.
+        let (get_ty, get) = match &bf.field_type {
+            FieldType::Enum(enum_def) => enum_def.decode(ty, get),
+            _ => (field_ty.clone(), get),
+        };
+
+        let with_doc = match &bf.field_type {
+            FieldType::Signed(_) => signed_setter_doc(&format_ident!("try_with_{}", field)),
+            _ => TokenStream2::new(),
+        };
+        accessors.extend(quote! {
+            #(#attrs)*
+            #[inline(always)]
+            pub fn #field(&self) -> #get_ty {
+                #get
+            }
+
+            #(#attrs)*
+            #with_doc
+            #[inline(always)]
+            #[must_use]
+            pub fn #with_fn(self, value: #field_ty) -> Self {
+                let bits: #ty = #to_bits;
+                Self((self.0 & !#reg::#mask) | ((bits << #reg::#shift) & #reg::#mask))
+            }
+        });
+        if let FieldType::Signed(signed_ty) = &bf.field_type {
+            let try_with = format_ident!("try_with_{}", field);
+            let fits = signed_fits(quote! { value }, quote! { #reg::#mask.count_ones() });
+            let doc = format!(
+                "Like [`{with_fn}`](Self::{with_fn}), but returns `value` back if it does not \
+                 fit the field."
+            );
+            accessors.extend(quote! {
+                #(#attrs)*
+                #[doc = ""]
+                #[doc = #doc]
+                #[inline(always)]
+                pub fn #try_with(self, value: #signed_ty) -> ::core::result::Result<Self, #signed_ty> {
+                    if !#fits {
+                        return Err(value);
+                    }
+                    let bits = value as #ty;
+                    Ok(Self((self.0 & !#reg::#mask) | ((bits << #reg::#shift) & #reg::#mask)))
                 }
-            }
+            });
+        }
+        if let Some(scale) = &bf.scale {
+            let signed = scale.signed(&bf.field_type);
+            let width = quote! { #reg::#mask.count_ones() };
+            let get = scale.to_physical(raw_bits.clone(), width.clone(), signed);
+            let to_bits = scale.to_bits(quote! { value }, width, signed, ty);
+            let field_str = field.to_string();
+            let (get_doc, set_doc) = (scale.doc(&field_str, false), scale.doc(&field_str, true));
+            let (f64_fn, f32_fn) = (
+                format_ident!("{}_f64", field),
+                format_ident!("{}_f32", field),
+            );
+            let with_f64 = format_ident!("with_{}_f64", field);
+            let with_f32 = format_ident!("with_{}_f32", field);
+            accessors.extend(quote! {
+                #get_doc
+                #[inline(always)]
+                pub fn #f64_fn(&self) -> f64 {
+                    #get
+                }
+
+                #get_doc
+                #[inline(always)]
+                pub fn #f32_fn(&self) -> f32 {
+                    self.#f64_fn() as f32
+                }
+
+                #set_doc
+                #[inline(always)]
+                #[must_use]
+                pub fn #with_f64(self, value: f64) -> Self {
+                    let bits: #ty = #to_bits;
+                    Self((self.0 & !#reg::#mask) | ((bits << #reg::#shift) & #reg::#mask))
+                }
+
+                #set_doc
+                #[inline(always)]
+                #[must_use]
+                pub fn #with_f32(self, value: f32) -> Self {
+                    self.#with_f64(value as f64)
+                }
+            });
         }
+        let field_str = field.to_string();
+        debug_fields.extend(quote! { .field(#field_str, &self.#field()) });
     }
-    doc
-}
 
-fn gen_enum_defs(vis: &Visibility, entries: &[RegisterEntry]) -> TokenStream2 {
-    let mut tokens = TokenStream2::new();
-    for entry in entries {
-        let ty = &entry.ty;
-        for bf in &entry.bitfields {
-            if let FieldType::Enum(enum_def) = &bf.field_type {
-                let ename = &enum_def.name;
-                let variant_names: Vec<_> = enum_def.variants.iter().map(|v| &v.name).collect();
-                let variant_values: Vec<_> = enum_def.variants.iter().map(|v| &v.value).collect();
+    let doc = format!("Value of register `{reg}`, decoded into its fields.");
+    let name_str = value_ty.to_string();
+    quote! {
+        #[doc = #doc]
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        pub struct #value_ty(#ty);
 
-                tokens.extend(quote! {
-                    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-                    #vis enum #ename {
-                        #(#variant_names,)*
-                    }
+        impl #value_ty {
+            /// Wraps raw register bits.
+            #[inline(always)]
+            pub const fn from_raw(raw: #ty) -> Self {
+                Self(raw)
+            }
 
-                    impl #ename {
-                        /// Convert from a raw register value.
-                        ///
-                        /// Unknown values map to the first declared variant.
-                        #[inline]
-                        #[allow(unreachable_patterns)]
-                        pub fn from_raw(v: #ty) -> Self {
-                            match v {
-                                #(#variant_values => Self::#variant_names,)*
-                                _ => {
-                                    let _variants = [#(Self::#variant_names,)*];
-                                    _variants[0]
-                                }
-                            }
-                        }
+            /// Raw register bits.
+            #[inline(always)]
+            pub const fn to_raw(self) -> #ty {
+                self.0
+            }
 
-                        /// Convert to raw register value.
-                        #[inline]
-                        pub fn to_raw(self) -> #ty {
-                            match self {
-                                #(Self::#variant_names => #variant_values as #ty,)*
-                            }
-                        }
-                    }
+            #accessors
+        }
 
-                    impl ::core::fmt::Display for #ename {
-                        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                            ::core::fmt::Debug::fmt(self, f)
-                        }
-                    }
-                });
+        /// The reset value.
+        impl ::core::default::Default for #value_ty {
+            fn default() -> Self {
+                Self(#reg::RESET)
             }
         }
-    }
-    tokens
-}
 
-fn gen_bounds_checks(bus: &Type, entries: &[RegisterEntry]) -> TokenStream2 {
-    let mut checks = TokenStream2::new();
-    for entry in entries {
-        let offset = &entry.offset;
-        let ty = &entry.ty;
-        let count_expr = match &entry.array_len {
-            Some(n) => quote! { (#n) },
-            None => quote! { 1usize },
-        };
-        checks.extend(quote! {
-            const _: () = assert!(
-                ::core::mem::size_of::<#ty>() <= ::core::mem::size_of::<#bus>(),
-                "register type must not be wider than bus type"
-            );
-            const _: () = assert!(
-                (#offset) % ::core::mem::align_of::<#bus>() == 0,
-                "register offset must be aligned to bus width"
-            );
-            if (#offset) + (#count_expr) * ::core::mem::size_of::<#bus>() > devmem.len() {
-                return None;
+        impl ::core::convert::From<#ty> for #value_ty {
+            fn from(raw: #ty) -> Self {
+                Self(raw)
             }
-        });
+        }
+
+        impl ::core::convert::From<#value_ty> for #ty {
+            fn from(value: #value_ty) -> Self {
+                value.0
+            }
+        }
+
+        impl ::core::fmt::Debug for #value_ty {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(#name_str)
+                    #debug_fields
+                    .finish()
+            }
+        }
     }
-    checks
 }
 
-fn gen_register_methods(vis: &Visibility, bus: &Type, entry: &RegisterEntry) -> TokenStream2 {
+fn gen_register_methods(
+    vis: &Visibility,
+    bus: &Type,
+    map_mod: &Ident,
+    entry: &RegisterEntry,
+) -> TokenStream2 {
     let name = &entry.name;
     let ty = &entry.ty;
     let offset = &entry.offset;
@@ -442,46 +1902,179 @@ fn gen_register_methods(vis: &Visibility, bus: &Type, entry: &RegisterEntry) ->
         }
     };
 
+    let kind_doc = kind_doc(entry.kind);
+    let read_attr = match entry.kind {
+        AccessKind::Rc => quote! { #[must_use = "reading clears the register"] },
+        _ => TokenStream2::new(),
+    };
+    let one_shot = one_shot_mask(map_mod, entry);
+
     if entry.kind.has_read() {
         methods.extend(quote! {
             #(#attrs)*
+            #kind_doc
+            #read_attr
             #[inline(always)]
             #vis fn #name(&self #idx_param) -> #ty {
                 #bounds
-                unsafe { ::core::ptr::read_volatile(self.devmem.as_ptr().add(#eff_offset) as *const #bus) as #ty }
+                unsafe { self.devmem.read_unchecked::<#bus>(#eff_offset) as #ty }
             }
         });
     }
 
+    let record_shadow = match shadow_place(entry) {
+        Some(shadow) => quote! { #shadow = value; },
+        None => TokenStream2::new(),
+    };
+
     if entry.kind.has_write() {
         methods.extend(quote! {
             #(#attrs)*
+            #kind_doc
             #[inline(always)]
             #vis fn #set_fn(&mut self #idx_param, value: #ty) {
                 #bounds
-                unsafe { ::core::ptr::write_volatile(self.devmem.as_ptr().add(#eff_offset) as *mut #bus, value as #bus) }
+                #record_shadow
+                unsafe { self.devmem.write_unchecked::<#bus>(#eff_offset, value as #bus) }
+            }
+        });
+    }
+
+    if entry.kind.has_reset() {
+        let reg = name.unraw();
+        let reset_fn = format_ident!("reset_{}", name);
+        let reset_body = match &entry.array_len {
+            Some(n) => quote! {
+                for idx in 0..(#n) {
+                    self.#set_fn(idx, #map_mod::#reg::RESET);
+                }
+            },
+            None => quote! { self.#set_fn(#map_mod::#reg::RESET); },
+        };
+        methods.extend(quote! {
+            /// Writes the reset value (to every element for arrays).
+            #[inline(always)]
+            #vis fn #reset_fn(&mut self) {
+                #reset_body
             }
         });
     }
 
     if entry.kind.has_modify() {
+        let (old, one_shot_doc) = match &one_shot {
+            Some(mask) => (
+                quote! { (val as #ty) & !#mask },
+                quote! {
+                    #[doc = ""]
+                    #[doc = "Write-one fields reach `f` as 0, so passing them through leaves them alone."]
+                },
+            ),
+            None => (quote! { val as #ty }, TokenStream2::new()),
+        };
         methods.extend(quote! {
             #(#attrs)*
+            #one_shot_doc
             #[inline(always)]
             #vis fn #modify_fn(&mut self #idx_param, f: impl FnOnce(#ty) -> #ty) {
                 #bounds
                 unsafe {
-                    let ptr = self.devmem.as_ptr().add(#eff_offset);
-                    let val = ::core::ptr::read_volatile(ptr as *const #bus) as #ty;
-                    ::core::ptr::write_volatile(ptr as *mut #bus, f(val) as #bus);
+                    self.devmem.modify_unchecked::<#bus>(#eff_offset, |val| f(#old) as #bus);
                 }
             }
         });
     }
 
+    // Whole-register value accessors
+    if !entry.bitfields.is_empty() {
+        let value_ty = value_type_ident(name);
+        let value_path = quote! { #map_mod::#value_ty };
+        let read_fn = format_ident!("read_{}", name);
+        let write_fn = format_ident!("write_{}", name);
+        let modify_fields_fn = format_ident!("modify_{}_fields", name);
+        if entry.kind.has_read() {
+            methods.extend(quote! {
+                /// Reads the register once and decodes all fields.
+                #kind_doc
+                #read_attr
+                #[inline(always)]
+                #vis fn #read_fn(&self #idx_param) -> #value_path {
+                    #bounds
+                    #value_path::from_raw(unsafe {
+                        self.devmem.read_unchecked::<#bus>(#eff_offset) as #ty
+                    })
+                }
+            });
+        }
+        if entry.kind.has_write() {
+            let write_fields_fn = format_ident!("write_{}_fields", name);
+            let (base, base_doc) = if entry.kind.is_one_shot() {
+                (quote! { #value_path::from_raw(0) }, "zero")
+            } else {
+                (quote! { #value_path::default() }, "the reset value")
+            };
+            let write_fields_doc = format!(
+                "Builds a value from {base_doc} with `f` and writes it with a single bus write, \
+                 without reading the register."
+            );
+            methods.extend(quote! {
+                /// Writes all fields with a single bus write.
+                #[inline(always)]
+                #vis fn #write_fn(&mut self #idx_param, value: #value_path) {
+                    #bounds
+                    let value = value.to_raw();
+                    #record_shadow
+                    unsafe { self.devmem.write_unchecked::<#bus>(#eff_offset, value as #bus) }
+                }
+
+                #[doc = #write_fields_doc]
+                #[inline(always)]
+                #vis fn #write_fields_fn(
+                    &mut self #idx_param,
+                    f: impl FnOnce(#value_path) -> #value_path,
+                ) {
+                    #bounds
+                    let value = f(#base).to_raw();
+                    #record_shadow
+                    unsafe { self.devmem.write_unchecked::<#bus>(#eff_offset, value as #bus) }
+                }
+            });
+        }
+        if entry.kind.has_modify() {
+            let (w, one_shot_doc) = match &one_shot {
+                Some(mask) => (
+                    quote! { #value_path::from_raw(r.to_raw() & !#mask) },
+                    quote! {
+                        #[doc = ""]
+                        #[doc = "Write-one fields are 0 in `w`, so only those set on it take effect."]
+                    },
+                ),
+                None => (quote! { r }, TokenStream2::new()),
+            };
+            methods.extend(quote! {
+                /// Reads the register, passes the value to `f` twice (`r` to
+                /// inspect, `w` to build on) and writes back what `f` returns —
+                /// one read and one write however many fields change.
+                #one_shot_doc
+                #[inline(always)]
+                #vis fn #modify_fields_fn(
+                    &mut self #idx_param,
+                    f: impl FnOnce(#value_path, #value_path) -> #value_path,
+                ) {
+                    #bounds
+                    unsafe {
+                        self.devmem.modify_unchecked::<#bus>(#eff_offset, |old| {
+                            let r = #value_path::from_raw(old as #ty);
+                            f(r, #w).to_raw() as #bus
+                        });
+                    }
+                }
+            });
+        }
+    }
+
     // Bitfield methods
     for bf in &entry.bitfields {
-        methods.extend(gen_bitfield_methods(vis, bus, entry, bf));
+        methods.extend(gen_bitfield_methods(vis, bus, map_mod, entry, bf));
     }
 
     methods
@@ -490,13 +2083,32 @@ fn gen_register_methods(vis: &Visibility, bus: &Type, entry: &RegisterEntry) ->
 fn gen_bitfield_methods(
     vis: &Visibility,
     bus: &Type,
+    map_mod: &Ident,
     entry: &RegisterEntry,
     bf: &Bitfield,
 ) -> TokenStream2 {
     let reg_name = &entry.name;
     let ty = &entry.ty;
     let offset = &entry.offset;
+    let kind = bf.kind(entry);
+    let kind_doc = kind_doc(kind);
     let bf_attrs = &bf.attrs;
+    let getter_attrs = match kind {
+        AccessKind::Rc => {
+            let whole = (entry.kind == AccessKind::Rc).then(|| {
+                let hint = format!("Use `read_{reg_name}()` to decode every field from one read.");
+                quote! { #[doc = #hint] }
+            });
+            quote! {
+                #(#bf_attrs)*
+                #kind_doc
+                #whole
+                #[must_use = "reading clears the register"]
+            }
+        }
+        _ => quote! { #(#bf_attrs)* #kind_doc },
+    };
+    let setter_attrs = quote! { #(#bf_attrs)* #kind_doc };
     let lo = &bf.lo;
     let hi = &bf.hi;
 
@@ -527,28 +2139,49 @@ fn gen_bitfield_methods(
     };
 
     let read_raw = quote! {
-        let raw = unsafe { ::core::ptr::read_volatile(self.devmem.as_ptr().add(#eff_offset) as *const #bus) } as #ty;
+        let raw = unsafe { self.devmem.read_unchecked::<#bus>(#eff_offset) } as #ty;
     };
 
-    let rmw_body = |value_expr: TokenStream2| {
-        quote! {
+    // Write-one registers take only the target bits. `wo` registers are
+    // never read: the field is merged into the shadow copy, which is then
+    // written. Elsewhere the write-one fields of the register are zeroed
+    // before writing back, so they are not triggered by accident.
+    let shadow = shadow_place(entry);
+    let keep = match one_shot_mask(map_mod, entry) {
+        Some(mask) => quote! { & !#mask },
+        None => TokenStream2::new(),
+    };
+    let rmw_body = |value_expr: TokenStream2| match &shadow {
+        _ if entry.kind.is_one_shot() => quote! {
+            #width_and_mask
+            let new = (#value_expr & mask) << (#lo);
+            unsafe { self.devmem.write_unchecked::<#bus>(#eff_offset, new as #bus) }
+        },
+        Some(shadow) => quote! {
+            #width_and_mask
+            let new = (#shadow & !(mask << (#lo))) | ((#value_expr & mask) << (#lo));
+            #shadow = new;
+            unsafe { self.devmem.write_unchecked::<#bus>(#eff_offset, new as #bus) }
+        },
+        None => quote! {
             #width_and_mask
             unsafe {
-                let ptr = self.devmem.as_ptr().add(#eff_offset);
-                let old = ::core::ptr::read_volatile(ptr as *const #bus) as #ty;
-                let new = (old & !(mask << (#lo))) | ((#value_expr & mask) << (#lo));
-                ::core::ptr::write_volatile(ptr as *mut #bus, new as #bus);
+                self.devmem.modify_unchecked::<#bus>(#eff_offset, |old| {
+                    let old = (old as #ty) #keep;
+                    let new = (old & !(mask << (#lo))) | ((#value_expr & mask) << (#lo));
+                    new as #bus
+                });
             }
-        }
+        },
     };
 
     let mut methods = TokenStream2::new();
 
     match &bf.field_type {
         FieldType::Raw => {
-            if entry.kind.has_read() {
+            if kind.has_read() {
                 methods.extend(quote! {
-                    #(#bf_attrs)*
+                    #getter_attrs
                     #[inline(always)]
                     #vis fn #getter_name(&self #idx_param) -> #ty {
                         #bounds
@@ -558,10 +2191,10 @@ fn gen_bitfield_methods(
                     }
                 });
             }
-            if entry.kind.has_write() {
+            if kind.has_write() {
                 let rmw = rmw_body(quote! { value });
                 methods.extend(quote! {
-                    #(#bf_attrs)*
+                    #setter_attrs
                     #[inline(always)]
                     #vis fn #setter_name(&mut self #idx_param, value: #ty) {
                         #bounds
@@ -571,9 +2204,9 @@ fn gen_bitfield_methods(
             }
         }
         FieldType::Bool => {
-            if entry.kind.has_read() {
+            if kind.has_read() {
                 methods.extend(quote! {
-                    #(#bf_attrs)*
+                    #getter_attrs
                     #[inline(always)]
                     #vis fn #getter_name(&self #idx_param) -> bool {
                         #bounds
@@ -583,10 +2216,10 @@ fn gen_bitfield_methods(
                     }
                 });
             }
-            if entry.kind.has_write() {
+            if kind.has_write() {
                 let rmw = rmw_body(quote! { value });
                 methods.extend(quote! {
-                    #(#bf_attrs)*
+                    #setter_attrs
                     #[inline(always)]
                     #vis fn #setter_name(&mut self #idx_param, value: bool) {
                         #bounds
@@ -597,9 +2230,9 @@ fn gen_bitfield_methods(
             }
         }
         FieldType::Cast(cast_ty) => {
-            if entry.kind.has_read() {
+            if kind.has_read() {
                 methods.extend(quote! {
-                    #(#bf_attrs)*
+                    #getter_attrs
                     #[inline(always)]
                     #vis fn #getter_name(&self #idx_param) -> #cast_ty {
                         #bounds
@@ -609,10 +2242,10 @@ fn gen_bitfield_methods(
                     }
                 });
             }
-            if entry.kind.has_write() {
+            if kind.has_write() {
                 let rmw = rmw_body(quote! { value });
                 methods.extend(quote! {
-                    #(#bf_attrs)*
+                    #setter_attrs
                     #[inline(always)]
                     #vis fn #setter_name(&mut self #idx_param, value: #cast_ty) {
                         #bounds
@@ -622,24 +2255,88 @@ fn gen_bitfield_methods(
                 });
             }
         }
+        FieldType::Signed(signed_ty) => {
+            if kind.has_read() {
+                let get = sign_extend(
+                    quote! { ((raw >> (#lo)) & mask) },
+                    quote! { width },
+                    signed_ty,
+                );
+                methods.extend(quote! {
+                    #getter_attrs
+                    #[inline(always)]
+                    #vis fn #getter_name(&self #idx_param) -> #signed_ty {
+                        #bounds
+                        #read_raw
+                        #width_and_mask
+                        #get
+                    }
+                });
+            }
+            if kind.has_write() {
+                let rmw = rmw_body(quote! { value });
+                let width = quote! { (#hi) - (#lo) + 1 };
+                let to_bits = signed_to_bits(
+                    format!("{reg_name}.{}", bf.name),
+                    quote! { value },
+                    width.clone(),
+                    ty,
+                );
+                let fits = signed_fits(quote! { value }, width);
+                let try_setter = format_ident!("try_set_{}_{}", reg_name, bf.name);
+                let panics = signed_setter_doc(&try_setter);
+                let try_doc = format!(
+                    "Like [`{setter_name}`](Self::{setter_name}), but returns `value` back \
+                     instead of writing if it does not fit the field."
+                );
+                methods.extend(quote! {
+                    #setter_attrs
+                    #panics
+                    #[inline(always)]
+                    #vis fn #setter_name(&mut self #idx_param, value: #signed_ty) {
+                        #bounds
+                        let value = #to_bits;
+                        #rmw
+                    }
+
+                    #setter_attrs
+                    #[doc = ""]
+                    #[doc = #try_doc]
+                    #[inline(always)]
+                    #vis fn #try_setter(
+                        &mut self #idx_param,
+                        value: #signed_ty,
+                    ) -> ::core::result::Result<(), #signed_ty> {
+                        #bounds
+                        if !#fits {
+                            return Err(value);
+                        }
+                        let value = value as #ty;
+                        #rmw;
+                        Ok(())
+                    }
+                });
+            }
+        }
         FieldType::Enum(enum_def) => {
             let ename = &enum_def.name;
-            if entry.kind.has_read() {
+            if kind.has_read() {
+                let (get_ty, get) = enum_def.decode(ty, quote! { ((raw >> (#lo)) & mask) });
                 methods.extend(quote! {
-                    #(#bf_attrs)*
+                    #getter_attrs
                     #[inline(always)]
-                    #vis fn #getter_name(&self #idx_param) -> #ename {
+                    #vis fn #getter_name(&self #idx_param) -> #get_ty {
                         #bounds
                         #read_raw
                         #width_and_mask
-                        #ename::from_raw((raw >> (#lo)) & mask)
+                        #get
                     }
                 });
             }
-            if entry.kind.has_write() {
+            if kind.has_write() {
                 let rmw = rmw_body(quote! { value });
                 methods.extend(quote! {
-                    #(#bf_attrs)*
+                    #setter_attrs
                     #[inline(always)]
                     #vis fn #setter_name(&mut self #idx_param, value: #ename) {
                         #bounds
@@ -651,6 +2348,109 @@ fn gen_bitfield_methods(
         }
     }
 
+    if let Some(scale) = &bf.scale {
+        let rmw = rmw_body(quote! { value });
+        methods.extend(gen_scaled_methods(
+            vis,
+            entry,
+            bf,
+            scale,
+            kind,
+            &getter_attrs,
+            &setter_attrs,
+            &read_raw,
+            &width_and_mask,
+            rmw,
+        ));
+    }
+
+    methods
+}
+
+/// `f64`/`f32` accessors of a scaled field, next to the raw ones.
+#[allow(clippy::too_many_arguments)]
+fn gen_scaled_methods(
+    vis: &Visibility,
+    entry: &RegisterEntry,
+    bf: &Bitfield,
+    scale: &Scale,
+    kind: AccessKind,
+    getter_attrs: &TokenStream2,
+    setter_attrs: &TokenStream2,
+    read_raw: &TokenStream2,
+    width_and_mask: &TokenStream2,
+    rmw: TokenStream2,
+) -> TokenStream2 {
+    let (reg_name, field, ty, lo) = (&entry.name, &bf.name, &entry.ty, &bf.lo);
+    let signed = scale.signed(&bf.field_type);
+    let (idx_param, idx_arg, bounds) = match &entry.array_len {
+        Some(n) => (
+            quote! { , idx: usize },
+            quote! { idx, },
+            quote! {
+                assert!(idx < (#n), concat!("index out of bounds for `", stringify!(#reg_name), "`"));
+            },
+        ),
+        None => (
+            TokenStream2::new(),
+            TokenStream2::new(),
+            TokenStream2::new(),
+        ),
+    };
+    let field_str = field.unraw().to_string();
+    let (get_doc, set_doc) = (scale.doc(&field_str, false), scale.doc(&field_str, true));
+
+    let mut methods = TokenStream2::new();
+    if kind.has_read() {
+        let get_f64 = format_ident!("{}_{}_f64", reg_name, field);
+        let get_f32 = format_ident!("{}_{}_f32", reg_name, field);
+        let get = scale.to_physical(quote! { ((raw >> (#lo)) & mask) }, quote! { width }, signed);
+        methods.extend(quote! {
+            #getter_attrs
+            #[doc = ""]
+            #get_doc
+            #[inline(always)]
+            #vis fn #get_f64(&self #idx_param) -> f64 {
+                #bounds
+                #read_raw
+                #width_and_mask
+                #get
+            }
+
+            #getter_attrs
+            #[doc = ""]
+            #get_doc
+            #[inline(always)]
+            #vis fn #get_f32(&self #idx_param) -> f32 {
+                self.#get_f64(#idx_arg) as f32
+            }
+        });
+    }
+    if kind.has_write() {
+        let set_f64 = format_ident!("set_{}_{}_f64", reg_name, field);
+        let set_f32 = format_ident!("set_{}_{}_f32", reg_name, field);
+        let hi = &bf.hi;
+        let to_bits = scale.to_bits(quote! { value }, quote! { (#hi) - (#lo) + 1 }, signed, ty);
+        methods.extend(quote! {
+            #setter_attrs
+            #[doc = ""]
+            #set_doc
+            #[inline(always)]
+            #vis fn #set_f64(&mut self #idx_param, value: f64) {
+                #bounds
+                let value = #to_bits;
+                #rmw
+            }
+
+            #setter_attrs
+            #[doc = ""]
+            #set_doc
+            #[inline(always)]
+            #vis fn #set_f32(&mut self #idx_param, value: f32) {
+                self.#set_f64(#idx_arg value as f64)
+            }
+        });
+    }
     methods
 }
 
@@ -673,16 +2473,51 @@ fn gen_web_impl(map: &RegisterMap) -> TokenStream2 {
             let ty = &entry.ty;
             let access_str = entry.kind.as_str();
             let doc_str = extract_doc_string(&entry.attrs);
+            let reset_info = match &entry.reset {
+                Some(_) => {
+                    let map_mod = map_mod_ident(map);
+                    let reg = entry.name.unraw();
+                    quote! { Some(#map_mod::#reg::RESET as u64) }
+                }
+                None => quote! { None },
+            };
 
             let mut bitfield_pushes = TokenStream2::new();
             for bf in &entry.bitfields {
                 let bf_name_str = bf.name.to_string();
                 let bf_doc = extract_doc_string(&bf.attrs);
+                let bf_access = bf.kind(entry).as_str();
+                let signed = match &bf.scale {
+                    Some(scale) => scale.signed(&bf.field_type),
+                    None => matches!(bf.field_type, FieldType::Signed(_)),
+                };
+                let (scale, offset, unit) = match &bf.scale {
+                    Some(scale) => {
+                        let (lsb, offset) = (&scale.lsb, scale.offset());
+                        let unit = match &scale.unit {
+                            Some(unit) => quote! { Some(#unit) },
+                            None => quote! { None },
+                        };
+                        (quote! { Some((#lsb) as f64) }, offset, unit)
+                    }
+                    None => (quote! { None }, quote! { 0.0 }, quote! { None }),
+                };
+                let decode = match &bf.field_type {
+                    FieldType::Enum(ed) => {
+                        let mode = ed.decode_str();
+                        quote! { Some(#mode) }
+                    }
+                    _ => quote! { None },
+                };
                 let lo = &bf.lo;
                 let hi = &bf.hi;
 
+                let fixed = bf.scale.as_ref().and_then(|scale| scale.fixed.as_ref());
                 let (ft_str, variants_expr) = match &bf.field_type {
-                    FieldType::Raw => ("raw".to_string(), quote! { Vec::new() }),
+                    FieldType::Raw => {
+                        let ft_str = fixed.map_or_else(|| "raw".to_string(), Fixed::format_str);
+                        (ft_str, quote! { Vec::new() })
+                    }
                     FieldType::Bool => (
                         "bool".to_string(),
                         quote! {
@@ -692,7 +2527,7 @@ fn gen_web_impl(map: &RegisterMap) -> TokenStream2 {
                             ]
                         },
                     ),
-                    FieldType::Cast(ct) => {
+                    FieldType::Cast(ct) | FieldType::Signed(ct) => {
                         let ct_str = quote!(#ct).to_string();
                         (ct_str, quote! { Vec::new() })
                     }
@@ -721,7 +2556,13 @@ fn gen_web_impl(map: &RegisterMap) -> TokenStream2 {
                         doc: #bf_doc,
                         lo: #lo,
                         hi: #hi,
+                        access: #bf_access,
                         field_type: #ft_str,
+                        signed: #signed,
+                        scale: #scale,
+                        offset: #offset,
+                        unit: #unit,
+                        decode: #decode,
                         variants: #variants_expr,
                     });
                 });
@@ -737,6 +2578,7 @@ fn gen_web_impl(map: &RegisterMap) -> TokenStream2 {
                         offset: __off,
                         access: #access_str,
                         width: ::core::mem::size_of::<#ty>() * 8,
+                        reset: #reset_info,
                         bitfields,
                     });
                 }
@@ -766,9 +2608,32 @@ fn gen_web_impl(map: &RegisterMap) -> TokenStream2 {
             }
         }
 
+        // Raw writes from the UI go into the `wo` shadows too, so the next
+        // field setter starts from what was written; the UI reads a `wo`
+        // register's shadow instead of the register.
+        let mut record_shadows = TokenStream2::new();
+        let mut read_shadows = TokenStream2::new();
+        for entry in map.entries.iter().filter(|e| e.kind == AccessKind::Wo) {
+            let ty = &entry.ty;
+            record_shadows.extend(shadow_at_offset(bus, entry, |shadow| {
+                quote! { #shadow = value as #bus as #ty; }
+            }));
+            read_shadows.extend(shadow_at_offset(bus, entry, |shadow| {
+                quote! { return Some(#shadow as u64); }
+            }));
+        }
+        let shadow_register = (!read_shadows.is_empty()).then(|| {
+            quote! {
+                fn shadow_register(&self, offset: usize) -> Option<u64> {
+                    #read_shadows
+                    None
+                }
+            }
+        });
+
         let name_str = name.to_string();
         quote! {
-            impl ::ddevmem::web::RegisterMapInfo for #name {
+            impl<B: ::ddevmem::Backend> ::ddevmem::web::RegisterMapInfo for #name<B> {
                 fn map_name(&self) -> &'static str {
                     #name_str
                 }
@@ -792,8 +2657,12 @@ fn gen_web_impl(map: &RegisterMap) -> TokenStream2 {
                 }
 
                 fn write_register(&mut self, offset: usize, value: u64) -> Option<()> {
-                    self.devmem.write::<#bus>(offset, value as #bus)
+                    self.devmem.write::<#bus>(offset, value as #bus)?;
+                    #record_shadows
+                    Some(())
                 }
+
+                #shadow_register
             }
         }
     }
@@ -805,50 +2674,118 @@ fn generate(map: RegisterMap) -> TokenStream2 {
     let name = &map.name;
     let bus = &map.bus;
 
-    // Generate enum definitions at module scope
-    let enum_defs = gen_enum_defs(vis, &map.entries);
+    // Enums live in the map module and are re-exported next to the map
+    let map_mod = map_mod_ident(&map);
+    let enum_reexports = gen_enum_reexports(vis, &map_mod, &map.entries);
 
     // Bounds checks in new()
     let bounds_checks = gen_bounds_checks(bus, &map.entries);
+    let extent = gen_extent(bus, &map.entries);
+    let (assoc_consts, map_module) = gen_map_module(vis, &map);
+    let layout_checks = gen_layout_checks(name, bus, &map.entries);
+    let (shadow_fields, shadow_inits) = gen_shadows(&map_mod, &map.entries);
+    let presets = gen_presets(bus, &map_mod, &map.entries);
+    let reset_all = gen_reset_all(vis, bus, &map_mod, &map.entries);
 
     // Register methods
     let mut all_methods = TokenStream2::new();
     for entry in &map.entries {
-        all_methods.extend(gen_register_methods(vis, bus, entry));
+        all_methods.extend(gen_register_methods(vis, bus, &map_mod, entry));
     }
 
     // Web impl (conditionally compiled)
     let web_impl = gen_web_impl(&map);
 
     quote! {
-        #enum_defs
+        #map_module
+
+        #enum_reexports
+
+        #layout_checks
 
         #(#attrs)*
-        #vis struct #name {
-            devmem: ::std::sync::Arc<::ddevmem::DevMem>,
+        #vis struct #name<B: ::ddevmem::Backend = ::ddevmem::DefaultBackend> {
+            devmem: ::std::sync::Arc<::ddevmem::DevMem<B>>,
+            /// Held only to release the claim on drop.
+            _region: ::core::option::Option<::ddevmem::Region>,
+            #shadow_fields
         }
 
-        impl #name {
+        impl<B: ::ddevmem::Backend> #name<B> {
             /// Creates a new register map wrapping the given [`DevMem`](::ddevmem::DevMem).
             ///
-            /// Returns `None` if any declared register offset falls outside the
-            /// mapped region.
+            /// # Errors
+            ///
+            /// Returns [`Error::RegisterOutOfBounds`](::ddevmem::Error::RegisterOutOfBounds),
+            /// naming the first register that falls outside the mapped region.
             ///
             /// # Safety
             ///
             /// The caller must ensure no other map or register aliases the same
-            /// memory range. [`DevMem`](::ddevmem::DevMem) does not track claimed regions.
+            /// memory range. This constructor does not consult the claim registry;
+            /// use [`claim`](Self::claim) to have overlaps detected instead.
             #[inline(always)]
-            pub unsafe fn new(devmem: ::std::sync::Arc<::ddevmem::DevMem>) -> Option<Self> {
+            pub unsafe fn new(
+                devmem: ::std::sync::Arc<::ddevmem::DevMem<B>>,
+            ) -> ::core::result::Result<Self, ::ddevmem::Error> {
+                #bounds_checks
+                #presets
+                Ok(Self { devmem, _region: None, #shadow_inits })
+            }
+
+            /// Creates a new register map after claiming its registers in
+            /// [`DevMem::claim`](::ddevmem::DevMem::claim).
+            ///
+            /// The claim spans offset `0` up to the end of the last register and
+            /// is released when the map is dropped.
+            ///
+            /// # Errors
+            ///
+            /// Returns [`Error::RegisterOutOfBounds`](::ddevmem::Error::RegisterOutOfBounds)
+            /// if a register falls outside the mapped region, or
+            /// [`Error::RegionClaimed`](::ddevmem::Error::RegionClaimed) if another
+            /// live map already claimed an overlapping range.
+            pub fn claim(
+                devmem: ::std::sync::Arc<::ddevmem::DevMem<B>>,
+            ) -> ::core::result::Result<Self, ::ddevmem::Error> {
                 #bounds_checks
-                Some(Self { devmem })
+                let region = devmem.claim(0, #extent, stringify!(#name))?;
+                #presets
+                Ok(Self { devmem, _region: Some(region), #shadow_inits })
             }
 
             #all_methods
+
+            #reset_all
+        }
+
+        impl #name {
+            /// Bytes spanned by the registers, from offset `0` to the end of the
+            /// last register (all elements for arrays). The mapping created by
+            /// [`open`](Self::open) covers the enclosing pages.
+            pub const REGION_SIZE: usize = #extent;
+
+            #assoc_consts
+
+            /// Maps [`REGION_SIZE`](Self::REGION_SIZE) bytes at physical address
+            /// `base` with [`DevMem::new`](::ddevmem::DevMem::new) and wraps them.
+            ///
+            /// # Errors
+            ///
+            /// Returns any error from [`DevMem::new`](::ddevmem::DevMem::new).
+            ///
+            /// # Safety
+            ///
+            /// Same contract as [`DevMem::new`](::ddevmem::DevMem::new) and
+            /// [`new`](Self::new).
+            pub unsafe fn open(base: usize) -> ::core::result::Result<Self, ::ddevmem::Error> {
+                let devmem = ::ddevmem::DevMem::new(base, Some(Self::REGION_SIZE))?;
+                Self::new(::std::sync::Arc::new(devmem))
+            }
         }
 
-        unsafe impl Sync for #name {}
-        unsafe impl Send for #name {}
+        unsafe impl<B: ::ddevmem::Backend> Sync for #name<B> {}
+        unsafe impl<B: ::ddevmem::Backend> Send for #name<B> {}
 
         #web_impl
     }
@@ -859,5 +2796,8 @@ fn generate(map: RegisterMap) -> TokenStream2 {
 #[proc_macro]
 pub fn register_map(input: TokenStream) -> TokenStream {
     let map = syn::parse_macro_input!(input as RegisterMap);
+    if let Err(e) = validate(&map) {
+        return e.to_compile_error().into();
+    }
     generate(map).into()
 }
@@ -3,11 +3,16 @@
 //!
 //! Demonstrated:
 //!   - Three different bus widths: `u32` (UART), `u16` (ADC), `u8` (I²C).
-//!   - All three access kinds: `rw`, `ro`, and `wo` (write-only / command).
+//!   - Access kinds: `rw`, `ro`, `wo` (write-only), `w1c` (write 1 to
+//!     clear), `wosc` (write 1 to trigger, self-clearing) and `rc` (read
+//!     clears), at register and bitfield level.
 //!   - Plain numeric bitfields (e.g. counters, addresses).
-//!   - Typed bitfields: `as bool`, `as u8`, and `as enum`.
+//!   - Typed bitfields: `as bool`, `as u8`, signed `as i8`, and `as enum`.
+//!   - Scaled (`scale .. offset .. unit "mV"`) and fixed-point
+//!     (`as fixed(UQ2.4)`) bitfields, shown in their unit in the UI.
 //!   - Read-only status registers with bool flags.
-//!   - Write-only command registers and write-1-to-clear interrupt registers.
+//!   - Self-clearing command registers and write-1-to-clear interrupt
+//!     registers, shown with Clear / Trigger buttons in the UI.
 //!   - **Register arrays** (`[T; N]`) — see the DMA peripheral below.
 //!
 //! Run with:
@@ -83,8 +88,8 @@ register_map! {
                 tx_en: 0 as bool,
                 /// Receiver enable.
                 rx_en: 1 as bool,
-                /// Number of stop bits.
-                stop:  2..=3 as enum StopBits {
+                /// Number of stop bits (3 is reserved and does not decode).
+                stop:  2..=3 as enum(result) StopBits {
                     One        = 0,
                     OnePointFive = 1,
                     Two        = 2,
@@ -124,7 +129,7 @@ register_map! {
             },
         0x0C =>
             /// Interrupt status (write 1 to clear the corresponding bit).
-            rw isr: u32 {
+            w1c isr: u32 = 0b0011 {
                 /// TX FIFO empty interrupt pending.
                 tx_empty: 0 as bool,
                 /// RX byte received interrupt pending.
@@ -135,9 +140,9 @@ register_map! {
                 frame:   3 as bool
             },
         0x10 =>
-            /// Command register (write-only). Writing triggers an action; the
-            /// hardware self-clears immediately.
-            wo cmd: u32 {
+            /// Command register. Writing 1 triggers an action; the hardware
+            /// self-clears immediately.
+            wosc cmd: u32 {
                 /// Reset transmitter (1 = reset).
                 tx_reset: 0 as bool,
                 /// Reset receiver (1 = reset).
@@ -151,8 +156,8 @@ register_map! {
             /// Transmit data register (write-only).
             wo txd: u32,
         0x18 =>
-            /// Received data register (read-only).
-            ro rxd: u32
+            /// Received data register (reading pops the RX FIFO).
+            rc rxd: u32
     }
 }
 
@@ -185,7 +190,11 @@ register_map! {
                     Timer1   = 1,
                     Timer2   = 2,
                     External = 3,
-                }
+                },
+                /// Start a software conversion (self-clearing).
+                wosc swstart: 10 as bool,
+                /// Overrun flag (write 1 to clear).
+                w1c ovr: 11 as bool
             },
         0x02 =>
             /// Last conversion result (read-only).
@@ -205,7 +214,23 @@ register_map! {
             },
         0x08 =>
             /// Trigger a software conversion (write any non-zero value).
-            wo start: u16
+            wo start: u16,
+        0x0A =>
+            /// Calibration trims (two's complement).
+            rw cal: u16 {
+                /// Bias trim in LSBs (-32..=31).
+                bias: 0..=5 as i8,
+                /// Gain trim in 1/256 steps (-16..=15).
+                gain: 8..=12 as i8
+            },
+        0x0C =>
+            /// Reference and input amplifier.
+            rw afe: u16 {
+                /// Reference voltage, 1000–3557.5 mV in 2.5 mV steps.
+                vref: 0..=9 scale 2.5 offset 1000 unit "mV",
+                /// Programmable gain, 0–3.9375 in 1/16 steps.
+                pga: 10..=15 as fixed(UQ2.4)
+            }
     }
 }
 
@@ -298,6 +323,10 @@ async fn main() {
     adc.set_cr_resolution(AdcResolution::Bits12);
     adc.set_cr_trigger(AdcTrigger::Timer1);
     adc.set_threshold(2048);
+    adc.set_cal_bias(-5);
+    adc.set_cal_gain(3);
+    adc.set_afe_vref_f64(2500.0);
+    adc.set_afe_pga_f64(1.5);
 
     i2c.set_cr_enable(true);
     i2c.set_cr_master(true);
@@ -329,8 +358,8 @@ async fn main() {
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8800").await.unwrap();
     println!("Showcase web UI at http://localhost:8800/hw");
-    println!("  uart (u32 bus)  — typed bitfields, wo command + txd, ro rxd");
-    println!("  adc  (u16 bus)  — enum trigger/resolution, ro data, wo start");
+    println!("  uart (u32 bus)  — typed bitfields, w1c isr, wosc command, wo txd, rc rxd");
+    println!("  adc  (u16 bus)  — enum trigger/resolution, wosc/w1c fields, signed trims, scaled AFE, wo start");
     println!("  i2c  (u8 bus)   — narrow bus, 7-bit address field, wo cmd");
     println!("  dma  (u32 bus)  — register arrays: fifo[0..8], chan[0..4]");
     axum::serve(listener, app).await.unwrap();
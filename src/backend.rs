@@ -0,0 +1,700 @@
+//! Storage backends behind [`DevMem`](crate::DevMem).
+//!
+//! A [`Backend`] performs the actual bus transactions at a byte offset. The
+//! crate ships two implementations:
+//!
+//! * [`Mmap`] (`device` feature) — a shared `mmap` of `/dev/mem`.
+//! * [`Emulator`] (`emulator` feature) — a zero-initialized heap buffer.
+//!
+//! Both can be compiled into the same binary, so real hardware and an
+//! emulated twin can be driven side by side. [`DefaultBackend`] is the one
+//! picked by [`DevMem::new`](crate::DevMem::new); when both features are
+//! enabled, the `default-device` or `default-emulator` feature chooses it.
+//!
+//! Custom backends (a remote JTAG probe, a logging wrapper, a simulator
+//! socket, …) only need to implement [`Backend`]:
+//!
+//! ```rust
+//! use std::sync::Mutex;
+//! use ddevmem::backend::{Backend, Word};
+//! use ddevmem::DevMem;
+//!
+//! /// Backend that stores every access in a `u64` per byte offset.
+//! struct Sparse(Mutex<std::collections::BTreeMap<usize, u64>>);
+//!
+//! impl Backend for Sparse {
+//!     fn len(&self) -> usize {
+//!         0x100
+//!     }
+//!
+//!     unsafe fn read<W: Word>(&self, offset: usize) -> W {
+//!         let map = self.0.lock().unwrap();
+//!         W::from_u64(map.get(&offset).copied().unwrap_or(0))
+//!     }
+//!
+//!     unsafe fn write<W: Word>(&self, offset: usize, value: W) {
+//!         self.0.lock().unwrap().insert(offset, value.to_u64());
+//!     }
+//! }
+//!
+//! let devmem = DevMem::from_backend(0x4000_0000, Sparse(Default::default()));
+//! devmem.write(0x10, 0xCAFEu32).unwrap();
+//! assert_eq!(devmem.read::<u32>(0x10), Some(0xCAFE));
+//! ```
+
+use bytemuck::{AnyBitPattern, NoUninit};
+
+#[cfg(feature = "device")]
+use crate::Error;
+
+#[cfg(feature = "device")]
+use memmap2::{MmapOptions, MmapRaw};
+
+#[cfg(feature = "device")]
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    os::unix::{fs::OpenOptionsExt, io::AsRawFd},
+    path::{Path, PathBuf},
+};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// An unsigned integer that can be transferred in a single bus access.
+///
+/// Implemented for `u8`, `u16`, `u32`, `u64` and `usize`. The trait is
+/// sealed: backends receive exactly these widths.
+pub trait Word: sealed::Sealed + AnyBitPattern + NoUninit + Send + Sync + 'static {
+    /// Width of the word in bits.
+    const BITS: u32;
+
+    /// Zero-extend to `u64`.
+    fn to_u64(self) -> u64;
+
+    /// Truncate a `u64` to this width.
+    fn from_u64(value: u64) -> Self;
+}
+
+macro_rules! impl_word {
+    ($($ty:ty),*) => {$(
+        impl sealed::Sealed for $ty {}
+
+        impl Word for $ty {
+            const BITS: u32 = <$ty>::BITS;
+
+            #[inline(always)]
+            fn to_u64(self) -> u64 {
+                self as u64
+            }
+
+            #[inline(always)]
+            fn from_u64(value: u64) -> Self {
+                value as $ty
+            }
+        }
+    )*};
+}
+
+impl_word!(u8, u16, u32, u64, usize);
+
+/// Performs volatile bus accesses at a byte offset within a region.
+///
+/// [`DevMem`](crate::DevMem) validates every offset against [`len`] before
+/// calling into the backend, so implementations do not need to repeat the
+/// bounds check.
+///
+/// [`len`]: Backend::len
+pub trait Backend: Send + Sync {
+    /// Length of the region in bytes.
+    fn len(&self) -> usize;
+
+    /// Returns `true` when the region has zero length.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads a `W` at `offset`.
+    ///
+    /// # Safety
+    ///
+    /// `offset + size_of::<W>()` must not exceed [`len`](Backend::len).
+    unsafe fn read<W: Word>(&self, offset: usize) -> W;
+
+    /// Writes `value` at `offset`.
+    ///
+    /// # Safety
+    ///
+    /// `offset + size_of::<W>()` must not exceed [`len`](Backend::len).
+    unsafe fn write<W: Word>(&self, offset: usize, value: W);
+
+    /// Read-modify-write of a `W` at `offset`.
+    ///
+    /// The default implementation is a plain [`read`](Backend::read)
+    /// followed by a [`write`](Backend::write). Backends with a native
+    /// read-modify-write transaction can override it.
+    ///
+    /// # Safety
+    ///
+    /// `offset + size_of::<W>()` must not exceed [`len`](Backend::len).
+    unsafe fn modify<W: Word>(&self, offset: usize, f: impl FnOnce(W) -> W) {
+        let value = self.read::<W>(offset);
+        self.write(offset, f(value));
+    }
+
+    /// Runs `f`, which accesses this backend, recovering from bus faults it
+    /// raises.
+    ///
+    /// Returns the offset of the first faulting access as `Err`; the value
+    /// produced by `f` is then meaningless. Used by fault-tolerant
+    /// [`DevMem`](crate::DevMem)s. The default implementation cannot recover
+    /// and simply runs `f`.
+    fn catch_faults<R>(&self, f: impl FnOnce() -> R) -> Result<R, usize> {
+        Ok(f())
+    }
+
+    /// Returns `true` if the region rejects writes; [`DevMem`](crate::DevMem)
+    /// then fails them with [`Error::ReadOnly`](crate::Error::ReadOnly)
+    /// instead of calling [`write`](Backend::write).
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// Seeds the power-on state of a register: stores `value` at `offset`
+    /// unless something was written there already.
+    ///
+    /// Register maps call this for every declared reset value when they are
+    /// created. Real hardware holds its reset state on its own, so the
+    /// default does nothing; [`Emulator`] stores the value.
+    ///
+    /// # Safety
+    ///
+    /// `offset + size_of::<W>()` must not exceed [`len`](Backend::len).
+    unsafe fn preset<W: Word>(&self, offset: usize, value: W) {
+        let _ = (offset, value);
+    }
+}
+
+/// Backend used by [`DevMem::new`](crate::DevMem::new).
+///
+/// With only one of `device` and `emulator` enabled this is that backend.
+/// With both, one of `default-device` or `default-emulator` must say which.
+#[cfg(any(
+    feature = "default-emulator",
+    all(feature = "emulator", not(feature = "device"))
+))]
+pub type DefaultBackend = Emulator;
+
+/// Backend used by [`DevMem::new`](crate::DevMem::new).
+///
+/// With only one of `device` and `emulator` enabled this is that backend.
+/// With both, one of `default-device` or `default-emulator` must say which.
+#[cfg(all(
+    any(
+        feature = "default-device",
+        all(feature = "device", not(feature = "emulator"))
+    ),
+    not(feature = "default-emulator")
+))]
+pub type DefaultBackend = Mmap;
+
+// ─── Device ──────────────────────────────────────────────────────────────────
+
+/// How [`Mmap`] opens and maps its file.
+///
+/// The default matches a plain `open(O_RDWR)` + `mmap(MAP_SHARED)`.
+/// Whether a `/dev/mem` mapping is cached depends on the architecture and
+/// kernel configuration; [`sync`](Self::sync) requests an uncached mapping
+/// on kernels that honour `O_SYNC` for `/dev/mem`.
+#[cfg(feature = "device")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MapOptions {
+    /// Open the file with `O_SYNC`.
+    pub sync: bool,
+    /// Open the file read-only and map it `PROT_READ`. Writes through
+    /// [`DevMem`](crate::DevMem) fail with
+    /// [`Error::ReadOnly`](crate::Error::ReadOnly).
+    pub read_only: bool,
+    /// Pre-fault the pages (`MAP_POPULATE`).
+    pub populate: bool,
+    /// Lock the pages in memory (`mlock`, as with `MAP_LOCKED`).
+    pub locked: bool,
+}
+
+/// Shared memory mapping of `/dev/mem` (or another mmap-able file).
+///
+/// `mmap` only accepts page-aligned file offsets, so the backend maps the
+/// enclosing page range and keeps the intra-page delta: offset `0` of the
+/// backend is always the byte that was asked for.
+///
+/// The `Debug` output names the file and the [`MapOptions`] in effect, e.g.
+/// `Mmap(/dev/mem, rw, sync)`.
+#[cfg(feature = "device")]
+pub struct Mmap {
+    /// `MmapRaw` hands out `*mut u8` from `&self`, which volatile writes
+    /// through a shared `DevMem` need, and supports read-only mappings.
+    map: MmapRaw,
+    /// Distance from the start of the page-aligned mapping to offset `0`.
+    delta: usize,
+    /// Requested length, excluding `delta`.
+    len: usize,
+    /// Mapped file and the (page-aligned) file offset of the mapping, kept
+    /// to restore pages replaced during fault recovery.
+    file: File,
+    file_offset: u64,
+    /// Set when neither the file nor a `PROT_NONE` placeholder could be
+    /// mapped back after a fault.
+    poisoned: std::sync::atomic::AtomicBool,
+    path: PathBuf,
+    options: MapOptions,
+}
+
+#[cfg(feature = "device")]
+impl Mmap {
+    /// Opens `/dev/mem` and maps `size` bytes starting at physical
+    /// `address`. The address does not need to be page-aligned.
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`DevMem::new`](crate::DevMem::new).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantOpenFile`] if `/dev/mem` cannot be opened, or
+    /// [`Error::CantMmapFile`] if the `mmap` call fails.
+    pub unsafe fn open(address: usize, size: usize) -> Result<Self, Error> {
+        Self::open_path("/dev/mem", address as u64, Some(size))
+    }
+
+    /// Opens `path` and maps `size` bytes starting at file offset `offset`
+    /// (any alignment).
+    ///
+    /// Any file supporting a shared `mmap` works: `/dev/mem`, `/dev/uioN`,
+    /// PCI `resourceN` files under sysfs, or a regular file. When `size` is
+    /// `None` the mapping extends to the end of the file as reported by
+    /// `stat`, or covers one page for character devices reporting size `0`.
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`DevMem::new`](crate::DevMem::new), applied to the
+    /// memory behind `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantOpenFile`] if `path` cannot be opened (or
+    /// inspected), or [`Error::CantMmapFile`] if the `mmap` call fails.
+    pub unsafe fn open_path(
+        path: impl AsRef<Path>,
+        offset: u64,
+        size: Option<usize>,
+    ) -> Result<Self, Error> {
+        Self::open_with(path, offset, size, MapOptions::default())
+    }
+
+    /// Like [`open_path`](Self::open_path), with explicit [`MapOptions`].
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`open_path`](Self::open_path).
+    ///
+    /// # Errors
+    ///
+    /// Same as [`open_path`](Self::open_path); [`Error::CantMmapFile`] also
+    /// covers a failed `mlock` when [`MapOptions::locked`] is set.
+    pub unsafe fn open_with(
+        path: impl AsRef<Path>,
+        offset: u64,
+        size: Option<usize>,
+        options: MapOptions,
+    ) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(!options.read_only)
+            .create(false)
+            .custom_flags(if options.sync { libc::O_SYNC } else { 0 })
+            .open(path)
+            .map_err(|source| Error::CantOpenFile {
+                path: path.to_owned(),
+                source,
+            })?;
+        Self::map_file(file, path.to_owned(), offset, size, options)
+    }
+
+    /// Maps `size` bytes of an already-open `file` starting at file offset
+    /// `offset` (any alignment), e.g. a `/dev/mem` descriptor received from
+    /// a privileged helper.
+    ///
+    /// [`MapOptions::read_only`] and [`MapOptions::sync`] are taken from the
+    /// descriptor's access mode and status flags. `size` defaults as in
+    /// [`open_path`](Self::open_path). The file is reported as
+    /// `/proc/self/fd/N` in errors and `Debug` output.
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`DevMem::new`](crate::DevMem::new), applied to the
+    /// memory behind `file`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantOpenFile`] if the descriptor cannot be
+    /// inspected, or [`Error::CantMmapFile`] if the `mmap` call fails.
+    pub unsafe fn from_file(file: File, offset: u64, size: Option<usize>) -> Result<Self, Error> {
+        let path = PathBuf::from(format!("/proc/self/fd/{}", file.as_raw_fd()));
+        let flags = libc::fcntl(file.as_raw_fd(), libc::F_GETFL);
+        if flags < 0 {
+            let source = std::io::Error::last_os_error();
+            return Err(Error::CantOpenFile { path, source });
+        }
+        let options = MapOptions {
+            sync: flags & libc::O_SYNC == libc::O_SYNC,
+            read_only: flags & libc::O_ACCMODE == libc::O_RDONLY,
+            ..MapOptions::default()
+        };
+        Self::map_file(file, path, offset, size, options)
+    }
+
+    unsafe fn map_file(
+        file: File,
+        path: PathBuf,
+        offset: u64,
+        size: Option<usize>,
+        options: MapOptions,
+    ) -> Result<Self, Error> {
+        let size = match size {
+            Some(size) => size,
+            None => {
+                let file_len = file
+                    .metadata()
+                    .map_err(|source| Error::CantOpenFile {
+                        path: path.clone(),
+                        source,
+                    })?
+                    .len();
+                default_len(file_len, offset)
+            }
+        };
+
+        let delta = (offset % page_size::get() as u64) as usize;
+        let file_offset = offset - delta as u64;
+        let mut map_options = MmapOptions::new();
+        map_options.len(delta + size).offset(file_offset);
+        if options.populate {
+            map_options.populate();
+        }
+        let map = if options.read_only {
+            map_options.map_raw_read_only(&file)
+        } else {
+            map_options.map_raw(&file)
+        };
+        let map = map.map_err(|source| Error::CantMmapFile {
+            path: path.clone(),
+            source,
+        })?;
+        if options.locked {
+            map.lock().map_err(|source| Error::CantMmapFile {
+                path: path.clone(),
+                source,
+            })?;
+        }
+
+        Ok(Self {
+            map,
+            delta,
+            len: size,
+            file,
+            file_offset,
+            poisoned: Default::default(),
+            path,
+            options,
+        })
+    }
+
+    /// Raw pointer to the byte at offset `0`, i.e. the requested address
+    /// rather than the start of the enclosing page.
+    ///
+    /// Writing through it is undefined behaviour for a
+    /// [read-only](MapOptions::read_only) mapping.
+    #[inline(always)]
+    pub fn as_ptr(&self) -> *mut u8 {
+        // SAFETY: `delta` lies within the mapping.
+        unsafe { self.map.as_mut_ptr().add(self.delta) }
+    }
+
+    /// File the mapping was created from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Options the mapping was created with.
+    pub fn options(&self) -> MapOptions {
+        self.options
+    }
+}
+
+/// Mapping length used when none is given: the rest of a file of
+/// `file_len` bytes, or one page for character devices reporting size `0`.
+#[cfg(feature = "device")]
+pub(crate) fn default_len(file_len: u64, offset: u64) -> usize {
+    if file_len == 0 {
+        page_size::get()
+    } else {
+        file_len.saturating_sub(offset) as usize
+    }
+}
+
+#[cfg(feature = "device")]
+impl fmt::Debug for Mmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let MapOptions {
+            sync,
+            read_only,
+            populate,
+            locked,
+        } = self.options;
+        write!(
+            f,
+            "Mmap({}, {}",
+            self.path.display(),
+            if read_only { "ro" } else { "rw" }
+        )?;
+        for (set, name) in [(sync, "sync"), (populate, "populate"), (locked, "locked")] {
+            if set {
+                write!(f, ", {name}")?;
+            }
+        }
+        f.write_str(")")
+    }
+}
+
+#[cfg(feature = "device")]
+impl Backend for Mmap {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    unsafe fn read<W: Word>(&self, offset: usize) -> W {
+        std::ptr::read_volatile(self.as_ptr().add(offset) as *const W)
+    }
+
+    #[inline(always)]
+    unsafe fn write<W: Word>(&self, offset: usize, value: W) {
+        std::ptr::write_volatile(self.as_ptr().add(offset) as *mut W, value)
+    }
+
+    #[inline(always)]
+    fn is_read_only(&self) -> bool {
+        self.options.read_only
+    }
+
+    /// Recovers from SIGBUS / SIGSEGV raised inside the mapping, e.g. by a
+    /// powered-down IP block or a page past the end of a mapped file.
+    ///
+    /// Faulting pages are temporarily backed by anonymous memory and the
+    /// whole mapping is re-established from the file before returning.
+    /// Other threads accessing the same pages meanwhile see that anonymous
+    /// memory, which is why `DevMem` requires external synchronization.
+    ///
+    /// If the file cannot be mapped again, the range is mapped `PROT_NONE`
+    /// instead: later guarded accesses fault (and retry the restore), and
+    /// unguarded ones crash rather than read stale memory. Should even that
+    /// fail, the mapping is poisoned and every guarded access reports a
+    /// fault at offset `0` without running.
+    fn catch_faults<R>(&self, f: impl FnOnce() -> R) -> Result<R, usize> {
+        use std::sync::atomic::Ordering;
+
+        if self.poisoned.load(Ordering::Relaxed) {
+            return Err(0);
+        }
+        let (base, map_len) = (self.map.as_ptr() as usize, self.map.len());
+        let (result, fault) = crate::fault::catch(base..base + map_len, f);
+        let Some(addr) = fault else {
+            return Ok(result);
+        };
+
+        let prot = if self.options.read_only {
+            libc::PROT_READ
+        } else {
+            libc::PROT_READ | libc::PROT_WRITE
+        };
+        let locked = if self.options.locked {
+            libc::MAP_LOCKED
+        } else {
+            0
+        };
+        // SAFETY: remaps the same file range over our own mapping, with
+        // the protection and flags it was created with.
+        let restored = unsafe {
+            libc::mmap(
+                base as *mut libc::c_void,
+                map_len,
+                prot,
+                libc::MAP_SHARED | libc::MAP_FIXED | locked,
+                self.file.as_raw_fd(),
+                self.file_offset as libc::off_t,
+            )
+        };
+        if restored == libc::MAP_FAILED {
+            // SAFETY: replaces our own mapping with inaccessible memory.
+            let fenced = unsafe {
+                libc::mmap(
+                    base as *mut libc::c_void,
+                    map_len,
+                    libc::PROT_NONE,
+                    libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_FIXED,
+                    -1,
+                    0,
+                )
+            };
+            if fenced == libc::MAP_FAILED {
+                self.poisoned.store(true, Ordering::Relaxed);
+            }
+        }
+        Err(addr.saturating_sub(base + self.delta))
+    }
+}
+
+// ─── Emulator ────────────────────────────────────────────────────────────────
+
+/// Heap-backed region for testing without hardware.
+///
+/// The buffer is allocated as `u64` words so every naturally aligned offset
+/// is also aligned in host memory. It starts zeroed; register maps with
+/// declared reset values [preset](Backend::preset) them on creation.
+#[cfg(feature = "emulator")]
+pub struct Emulator {
+    buf: Box<[std::cell::UnsafeCell<u64>]>,
+    /// One bit per byte of `buf`, set once the byte has been written, so
+    /// presets never clobber written state.
+    written: Box<[std::sync::atomic::AtomicU8]>,
+    len: usize,
+}
+
+// SAFETY: Volatile MMIO accesses are inherently thread-unsafe at the hardware
+// level. We declare Send + Sync here and delegate synchronization responsibility
+// to the caller (Arc<Mutex<RegisterMap>> is the recommended pattern). This
+// mirrors how AtomicXxx types work: they use UnsafeCell and assert Sync.
+#[cfg(feature = "emulator")]
+unsafe impl Send for Emulator {}
+#[cfg(feature = "emulator")]
+unsafe impl Sync for Emulator {}
+
+#[cfg(feature = "emulator")]
+impl Emulator {
+    /// Allocates a zero-initialized region of `size` bytes.
+    pub fn new(size: usize) -> Self {
+        let words = size.div_ceil(std::mem::size_of::<u64>());
+        let buf = (0..words).map(|_| std::cell::UnsafeCell::new(0)).collect();
+        let written = (0..words)
+            .map(|_| std::sync::atomic::AtomicU8::new(0))
+            .collect();
+        Self {
+            buf,
+            written,
+            len: size,
+        }
+    }
+
+    /// Marks `len` bytes at `offset` as written; returns whether any of
+    /// them already were.
+    fn mark_written(&self, offset: usize, len: usize) -> bool {
+        use std::sync::atomic::Ordering;
+        let mut any = false;
+        for byte in offset..offset + len {
+            let bit = 1 << (byte % 8);
+            any |= self.written[byte / 8].fetch_or(bit, Ordering::Relaxed) & bit != 0;
+        }
+        any
+    }
+
+    fn is_written(&self, offset: usize, len: usize) -> bool {
+        use std::sync::atomic::Ordering;
+        (offset..offset + len)
+            .any(|byte| self.written[byte / 8].load(Ordering::Relaxed) & (1 << (byte % 8)) != 0)
+    }
+
+    /// Raw pointer to the first byte of the buffer.
+    #[inline(always)]
+    pub fn as_ptr(&self) -> *mut u8 {
+        // `UnsafeCell<u64>` is `repr(transparent)`, so the slice is a
+        // contiguous run of bytes that may be mutated through `&self`.
+        self.buf.as_ptr() as *mut u8
+    }
+}
+
+#[cfg(feature = "emulator")]
+impl std::fmt::Debug for Emulator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Emulator({:#X} bytes)", self.len)
+    }
+}
+
+#[cfg(feature = "emulator")]
+impl Backend for Emulator {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    unsafe fn read<W: Word>(&self, offset: usize) -> W {
+        std::ptr::read_volatile(self.as_ptr().add(offset) as *const W)
+    }
+
+    #[inline(always)]
+    unsafe fn write<W: Word>(&self, offset: usize, value: W) {
+        self.mark_written(offset, std::mem::size_of::<W>());
+        std::ptr::write_volatile(self.as_ptr().add(offset) as *mut W, value)
+    }
+
+    unsafe fn preset<W: Word>(&self, offset: usize, value: W) {
+        if !self.is_written(offset, std::mem::size_of::<W>()) {
+            std::ptr::write_volatile(self.as_ptr().add(offset) as *mut W, value)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "device"))]
+mod tests {
+    use crate::{DevMem, Error};
+
+    #[test]
+    fn faults_are_recovered_and_the_mapping_restored() {
+        let page = page_size::get();
+        let path = std::env::temp_dir().join(format!("ddevmem-fault-{}", std::process::id()));
+        std::fs::write(&path, vec![0x5Au8; page]).unwrap();
+        // The second page lies past the end of the file: touching it raises
+        // SIGBUS.
+        let devmem = unsafe { DevMem::builder().path(&path).len(2 * page).build().unwrap() }
+            .with_fault_tolerance(true);
+
+        let fault = devmem.try_read::<u32>(page);
+        assert!(matches!(fault, Err(Error::BusFault { offset }) if offset == page));
+        assert_eq!(devmem.try_read::<u8>(0).unwrap(), 0x5A);
+        assert!(matches!(
+            devmem.try_write(page + 4, 1u32),
+            Err(Error::BusFault { .. })
+        ));
+        devmem.try_write(4, 0xA5u8).unwrap();
+
+        // The file is mapped again after each fault, so growing it makes
+        // the page reachable.
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(2 * page as u64).unwrap();
+        devmem.try_write(page, 0x1234_5678u32).unwrap();
+        assert_eq!(devmem.try_read::<u32>(page).unwrap(), 0x1234_5678);
+
+        let view = devmem.slice(page - 8, 16).unwrap();
+        file.set_len(page as u64).unwrap();
+        assert!(matches!(
+            view.try_read::<u64>(8),
+            Err(Error::BusFault { offset: 8 })
+        ));
+        assert_eq!(view.try_read::<u8>(0).unwrap(), 0x5A);
+        assert_eq!(devmem.try_read::<u8>(4).unwrap(), 0xA5);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,67 @@
+//! `ddevmem-broker` — hands out policy-checked device descriptors to
+//! unprivileged clients. See `ddevmem::broker` for the policy format.
+
+use std::{path::PathBuf, process::ExitCode};
+
+use ddevmem::broker::{Broker, Policy};
+
+const USAGE: &str = "usage: ddevmem-broker [--policy FILE] [--socket PATH]";
+
+fn main() -> ExitCode {
+    let mut policy = PathBuf::from("/etc/ddevmem/broker.conf");
+    let mut socket = PathBuf::from("/run/ddevmem-broker.sock");
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let target = match arg.as_str() {
+            "--policy" => &mut policy,
+            "--socket" => &mut socket,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ => {
+                eprintln!("unknown argument `{arg}`\n{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        };
+        match args.next() {
+            Some(value) => *target = PathBuf::from(value),
+            None => {
+                eprintln!("`{arg}` needs a value\n{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let broker = match Policy::load(&policy).and_then(|p| Broker::bind(&socket, p)) {
+        Ok(broker) => broker,
+        Err(e) => {
+            eprintln!("ddevmem-broker: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!(
+        "ddevmem-broker: serving {} on {} ({} rules)",
+        broker.policy().device().display(),
+        socket.display(),
+        broker.policy().rules().len()
+    );
+
+    let served = broker.serve_with(|result| match result {
+        Ok(g) => eprintln!(
+            "ddevmem-broker: granted uid {} (pid {}) {:#X}..{:#X} {}",
+            g.uid,
+            g.pid,
+            g.address,
+            g.address + g.len,
+            if g.writable { "rw" } else { "ro" }
+        ),
+        Err(e) => eprintln!("ddevmem-broker: {e}"),
+    });
+    if let Err(e) = served {
+        eprintln!("ddevmem-broker: accept failed: {e}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
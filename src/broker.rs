@@ -0,0 +1,1017 @@
+//! Privileged region broker.
+//!
+//! Opening device memory usually requires root. The broker is a small
+//! daemon that runs as root, checks requests against a [`Policy`] of ranges
+//! allowed per Unix user or group, and hands the client an open descriptor over a
+//! Unix domain socket (`SCM_RIGHTS`). [`map`] on the client side turns that
+//! descriptor into an ordinary [`DevMem`], so register maps work unchanged.
+//!
+//! The `ddevmem-broker` binary (`broker` feature) wraps [`Broker`]:
+//!
+//! ```text
+//! ddevmem-broker --policy /etc/ddevmem/broker.conf --socket /run/ddevmem-broker.sock
+//! ```
+//!
+//! A descriptor is not confined to the granted range: a client can map any
+//! other offset of the device it was handed, and even a read-only mapping
+//! can trigger read side effects. The broker therefore never serves
+//! `/dev/mem`, which would reach all of physical memory and every register
+//! behind it. [`device`](Policy::device) must name a node exposing only the
+//! hardware in question (a UIO device, a PCI `resourceN` file); addresses
+//! are offsets into that file, and anyone granted a range of it can reach
+//! all of it. Read-only grants are opened `O_RDONLY`.
+//!
+//! # Policy file
+//!
+//! ```text
+//! # device to open; required, and never /dev/mem
+//! device /dev/uio0
+//!
+//! # who          name or id   address   length   mode
+//! user           alice        0x0       0x1000   rw
+//! group          fpga         0x0       0x1000   ro
+//! ```
+//!
+//! A request is granted if a rule for the peer's uid, or for one of its
+//! groups, covers the whole range; `rw` requests need an `rw` rule.
+//!
+//! # Example
+//!
+//! A regular file stands in for the device:
+//!
+//! ```rust
+//! use std::thread;
+//! use ddevmem::broker::{self, Broker, Policy};
+//! use ddevmem::Error;
+//!
+//! let dir = std::env::temp_dir().join("ddevmem-broker-doctest");
+//! # let _ = std::fs::remove_dir_all(&dir);
+//! std::fs::create_dir_all(&dir).unwrap();
+//! let device = dir.join("mem");
+//! std::fs::write(&device, vec![0u8; 0x3000]).unwrap();
+//!
+//! let uid = unsafe { libc::getuid() };
+//! let policy = Policy::parse(&format!(
+//!     "device {}\nuser {uid} 0x1000 0x1000 rw\nuser {uid} 0x2000 0x1000 ro\n",
+//!     device.display()
+//! ))
+//! .unwrap();
+//!
+//! let socket = dir.join("broker.sock");
+//! let broker = Broker::bind(&socket, policy).unwrap();
+//! thread::spawn(move || broker.serve());
+//!
+//! let regs = unsafe { broker::map(&socket, 0x1000, 0x100).unwrap() };
+//! assert_eq!(regs.address(), 0x1000);
+//! regs.write(0x10, 0xCAFEu32).unwrap();
+//!
+//! let ro = unsafe { broker::map_read_only(&socket, 0x2000, 0x100).unwrap() };
+//! assert_eq!(ro.write(0x10, 0u32), None);
+//!
+//! let denied = unsafe { broker::map(&socket, 0x2000, 0x100) }.unwrap_err();
+//! assert!(matches!(denied, Error::Denied { address: 0x2000, .. }));
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+
+use std::{
+    ffi::{CStr, CString},
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    mem,
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+        unix::{
+            fs::{FileTypeExt, MetadataExt, OpenOptionsExt, PermissionsExt},
+            net::{UnixListener, UnixStream},
+        },
+    },
+    path::{Path, PathBuf},
+    ptr,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{backend::Mmap, policy::parse_num, DevMem, Error};
+
+/// Longest request or reply line accepted, in bytes.
+const MAX_LINE: usize = 256;
+
+/// How long the broker waits for a client to send its whole request,
+/// unless changed with [`Broker::with_request_timeout`].
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many clients [`Broker::serve`] handles at once, unless changed with
+/// [`Broker::with_max_clients`].
+const MAX_CLIENTS: usize = 16;
+
+// ─── Policy ──────────────────────────────────────────────────────────────────
+
+/// Whom a [`Rule`] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Principal {
+    /// A Unix user id.
+    User(u32),
+    /// A Unix group id, matched against the peer's primary and
+    /// supplementary groups.
+    Group(u32),
+}
+
+/// One allowed physical range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    /// Who may request the range.
+    pub who: Principal,
+    /// First physical address of the range.
+    pub address: u64,
+    /// Length of the range in bytes.
+    pub len: u64,
+    /// Whether read-write requests are allowed (`rw`) or only read-only
+    /// ones (`ro`).
+    pub writable: bool,
+}
+
+/// Allowlist of physical ranges per user or group; see the
+/// [module documentation](self) for the file format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    device: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl Policy {
+    /// Reads and parses a policy file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantReadFile`] if the file cannot be read or a line
+    /// is malformed; the error names the offending line.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .and_then(|text| Self::parse(&text))
+            .map_err(|source| Error::CantReadFile {
+                path: path.to_owned(),
+                source,
+            })
+    }
+
+    /// Parses policy text. User and group names are resolved through the
+    /// system databases; numeric ids are taken as-is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error naming the first
+    /// malformed line, or if the `device` line is missing or names
+    /// `/dev/mem`.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut device = None;
+        let mut rules = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let invalid = |msg: &str| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {msg}", n + 1))
+            };
+            match fields.as_slice() {
+                ["device", path] => {
+                    let path = PathBuf::from(path);
+                    if is_dev_mem(&path) {
+                        return Err(invalid(&format!(
+                            "{} exposes all of physical memory, which the broker never \
+                             serves; use a device exposing only the hardware in question",
+                            path.display()
+                        )));
+                    }
+                    device = Some(path);
+                }
+                [kind @ ("user" | "group"), name, address, len, mode] => {
+                    let who = if *kind == "user" {
+                        Principal::User(lookup_user(name).ok_or_else(|| invalid("unknown user"))?)
+                    } else {
+                        Principal::Group(
+                            lookup_group(name).ok_or_else(|| invalid("unknown group"))?,
+                        )
+                    };
+                    let address = parse_num(address).ok_or_else(|| invalid("bad address"))?;
+                    let len = parse_num(len).ok_or_else(|| invalid("bad length"))?;
+                    let writable = match *mode {
+                        "rw" => true,
+                        "ro" => false,
+                        _ => return Err(invalid("mode must be `rw` or `ro`")),
+                    };
+                    rules.push(Rule {
+                        who,
+                        address,
+                        len,
+                        writable,
+                    });
+                }
+                _ => {
+                    return Err(invalid(
+                        "expected `device PATH` or `user|group NAME ADDR LEN MODE`",
+                    ))
+                }
+            }
+        }
+        let device = device.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "missing `device PATH` line")
+        })?;
+        Ok(Self { device, rules })
+    }
+
+    /// Device the broker opens for granted requests.
+    pub fn device(&self) -> &Path {
+        &self.device
+    }
+
+    /// Rules in file order.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Whether a peer with `uid` and `groups` may map `len` bytes at
+    /// `address`, read-write if `writable`.
+    pub fn allows(&self, uid: u32, groups: &[u32], address: u64, len: u64, writable: bool) -> bool {
+        let Some(end) = address.checked_add(len) else {
+            return false;
+        };
+        self.rules.iter().any(|rule| {
+            let who = match rule.who {
+                Principal::User(u) => u == uid,
+                Principal::Group(g) => groups.contains(&g),
+            };
+            who && (rule.writable || !writable)
+                && address >= rule.address
+                && rule
+                    .address
+                    .checked_add(rule.len)
+                    .is_some_and(|rule_end| end <= rule_end)
+        })
+    }
+}
+
+impl FromStr for Policy {
+    type Err = io::Error;
+
+    fn from_str(text: &str) -> io::Result<Self> {
+        Self::parse(text)
+    }
+}
+
+// ─── Server ──────────────────────────────────────────────────────────────────
+
+/// A request the broker granted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Grant {
+    /// User id of the client.
+    pub uid: u32,
+    /// Process id of the client.
+    pub pid: i32,
+    /// First physical address granted.
+    pub address: u64,
+    /// Length granted in bytes.
+    pub len: u64,
+    /// Whether the descriptor was opened read-write.
+    pub writable: bool,
+}
+
+/// Server side: listens on a Unix socket and hands out descriptors allowed
+/// by a [`Policy`].
+#[derive(Debug)]
+pub struct Broker {
+    listener: UnixListener,
+    policy: Policy,
+    timeout: Duration,
+    max_clients: usize,
+}
+
+impl Broker {
+    /// Binds `socket` (replacing a stale socket file) and makes it
+    /// connectable by every user; access is decided per request by `policy`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantOpenFile`] if the socket cannot be created.
+    pub fn bind(socket: impl AsRef<Path>, policy: Policy) -> Result<Self, Error> {
+        let socket = socket.as_ref();
+        let err = |source| Error::CantOpenFile {
+            path: socket.to_owned(),
+            source,
+        };
+        match fs::remove_file(socket) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(err(e)),
+            _ => {}
+        }
+        let listener = UnixListener::bind(socket).map_err(err)?;
+        fs::set_permissions(socket, fs::Permissions::from_mode(0o666)).map_err(err)?;
+        Ok(Self::from_listener(listener, policy))
+    }
+
+    /// Serves requests on an already-bound listener, e.g. one inherited
+    /// through socket activation.
+    pub fn from_listener(listener: UnixListener, policy: Policy) -> Self {
+        Self {
+            listener,
+            policy,
+            timeout: REQUEST_TIMEOUT,
+            max_clients: MAX_CLIENTS,
+        }
+    }
+
+    /// Sets how long a client may take to send its whole request before it
+    /// is dropped (default five seconds).
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how many clients [`serve`](Self::serve) handles at once
+    /// (default 16). Clients beyond that get an error reply straight away.
+    pub fn with_max_clients(mut self, max_clients: usize) -> Self {
+        self.max_clients = max_clients;
+        self
+    }
+
+    /// The listening socket, for callers running their own accept loop
+    /// around [`handle`](Self::handle).
+    pub fn listener(&self) -> &UnixListener {
+        &self.listener
+    }
+
+    /// Policy requests are checked against.
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    /// Accepts and handles clients forever, each on its own thread. Failed
+    /// or denied requests are reported to the client and otherwise ignored.
+    ///
+    /// The socket is open to every user, so the number of clients handled
+    /// at once is capped (see [`with_max_clients`](Self::with_max_clients)).
+    /// A client connecting while the cap is reached is told the broker is
+    /// busy and disconnected.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `accept` if it fails for a reason other than an
+    /// interrupted call or an aborted connection.
+    pub fn serve(&self) -> io::Result<()> {
+        self.serve_with(|_| {})
+    }
+
+    /// Like [`serve`](Self::serve), but passes the outcome of every request
+    /// to `report`, e.g. for logging.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`serve`](Self::serve).
+    pub fn serve_with(&self, report: impl Fn(Result<Grant, Error>) + Sync) -> io::Result<()> {
+        let report = &report;
+        let active = &AtomicUsize::new(0);
+        thread::scope(|scope| loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::Interrupted | io::ErrorKind::ConnectionAborted
+                    ) =>
+                {
+                    continue
+                }
+                Err(e) => return Err(e),
+            };
+            // Only this loop increments, so the check cannot race.
+            if active.load(Ordering::Acquire) >= self.max_clients {
+                report(Err(self.refuse_busy(&stream)));
+                continue;
+            }
+            active.fetch_add(1, Ordering::AcqRel);
+            scope.spawn(move || {
+                report(self.handle(&stream));
+                active.fetch_sub(1, Ordering::AcqRel);
+            });
+        })
+    }
+
+    /// Reads one request from `stream`, answers it, and returns what was
+    /// granted. A client that has not sent a complete request line within
+    /// the [request timeout](Self::with_request_timeout) is dropped, however
+    /// slowly it trickles bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Denied`] if the request is malformed or not covered
+    /// by the policy, [`Error::CantOpenFile`] if the device cannot be
+    /// opened, or [`Error::CantReadFile`] if reading the request fails. The
+    /// client is told about each of these. Failing to send the reply is
+    /// also reported as [`Error::CantReadFile`].
+    pub fn handle(&self, stream: &UnixStream) -> Result<Grant, Error> {
+        let result = self.answer(stream);
+        let sent = match &result {
+            Ok((_, file)) => send_with_fd(stream, b"ok\n", Some(file.as_raw_fd())),
+            Err(Error::Denied { reason, .. }) => {
+                send_with_fd(stream, reply_line("denied", reason).as_bytes(), None)
+            }
+            Err(e) => send_with_fd(stream, reply_line("error", &e.to_string()).as_bytes(), None),
+        };
+        let (grant, _) = result?;
+        sent.map_err(|source| Error::CantReadFile {
+            path: PathBuf::from("<client>"),
+            source,
+        })?;
+        Ok(grant)
+    }
+
+    fn answer(&self, stream: &UnixStream) -> Result<(Grant, File), Error> {
+        let io_err = |source| Error::CantReadFile {
+            path: PathBuf::from("<client>"),
+            source,
+        };
+        let cred = peer_cred(stream).map_err(io_err)?;
+        let line = read_line(stream, Instant::now() + self.timeout).map_err(io_err)?;
+        self.grant(&cred, &line)
+    }
+
+    /// Tells a client over the cap that the broker is busy.
+    fn refuse_busy(&self, stream: &UnixStream) -> Error {
+        let message = format!("busy: {} clients already connected", self.max_clients);
+        // Best effort; the client is dropped either way.
+        let _ = stream.set_nonblocking(true);
+        let _ = send_with_fd(stream, reply_line("error", &message).as_bytes(), None);
+        Error::CantReadFile {
+            path: PathBuf::from("<client>"),
+            source: io::Error::new(io::ErrorKind::WouldBlock, message),
+        }
+    }
+
+    fn grant(&self, cred: &libc::ucred, line: &str) -> Result<(Grant, File), Error> {
+        let (address, len, writable) = parse_request(line).ok_or_else(|| Error::Denied {
+            address: 0,
+            len: 0,
+            reason: format!("malformed request {line:?}"),
+        })?;
+        let denied = |reason: String| Error::Denied {
+            address: address as usize,
+            len: len as usize,
+            reason,
+        };
+
+        // Checked again here in case the device node changed since the
+        // policy was parsed.
+        if is_dev_mem(&self.policy.device) {
+            return Err(denied(format!(
+                "descriptors for {} are never handed out",
+                self.policy.device.display()
+            )));
+        }
+
+        let groups = groups_of(cred.uid, cred.gid);
+        if !self
+            .policy
+            .allows(cred.uid, &groups, address, len, writable)
+        {
+            return Err(denied(format!(
+                "uid {} may not map {address:#X}..{:#X} {}",
+                cred.uid,
+                address.saturating_add(len),
+                if writable { "rw" } else { "ro" }
+            )));
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(writable)
+            .custom_flags(libc::O_SYNC)
+            .open(&self.policy.device)
+            .map_err(|source| Error::CantOpenFile {
+                path: self.policy.device.clone(),
+                source,
+            })?;
+        let grant = Grant {
+            uid: cred.uid,
+            pid: cred.pid,
+            address,
+            len,
+            writable,
+        };
+        Ok((grant, file))
+    }
+}
+
+// ─── Client ──────────────────────────────────────────────────────────────────
+
+/// Asks the broker listening on `socket` for a read-write mapping of `len`
+/// bytes at physical `address`.
+///
+/// # Safety
+///
+/// Same contract as [`DevMem::new`].
+///
+/// # Errors
+///
+/// Returns [`Error::Denied`] if the broker refuses, [`Error::CantOpenFile`]
+/// / [`Error::CantReadFile`] if the socket cannot be used or the broker
+/// reports a failure of its own, or any error from [`DevMem::from_fd`].
+pub unsafe fn map(
+    socket: impl AsRef<Path>,
+    address: usize,
+    len: usize,
+) -> Result<DevMem<Mmap>, Error> {
+    request(socket.as_ref(), address, len, true)
+}
+
+/// Like [`map`], but asks for a read-only descriptor; the resulting
+/// `DevMem` rejects writes.
+///
+/// # Safety
+///
+/// Same contract as [`DevMem::new`].
+///
+/// # Errors
+///
+/// Same as [`map`].
+pub unsafe fn map_read_only(
+    socket: impl AsRef<Path>,
+    address: usize,
+    len: usize,
+) -> Result<DevMem<Mmap>, Error> {
+    request(socket.as_ref(), address, len, false)
+}
+
+unsafe fn request(
+    socket: &Path,
+    address: usize,
+    len: usize,
+    writable: bool,
+) -> Result<DevMem<Mmap>, Error> {
+    let open_err = |source| Error::CantOpenFile {
+        path: socket.to_owned(),
+        source,
+    };
+    let read_err = |source| Error::CantReadFile {
+        path: socket.to_owned(),
+        source,
+    };
+
+    let mut stream = UnixStream::connect(socket).map_err(open_err)?;
+    let mode = if writable { "rw" } else { "ro" };
+    writeln!(stream, "map {address:#X} {len:#X} {mode}").map_err(open_err)?;
+
+    let (reply, fd) = recv_with_fd(&stream).map_err(read_err)?;
+    match (reply.trim_end().split_once(' '), reply.trim_end(), fd) {
+        (_, "ok", Some(fd)) => DevMem::from_fd(fd, address as u64, len),
+        (Some(("denied", reason)), _, _) => Err(Error::Denied {
+            address,
+            len,
+            reason: reason.to_owned(),
+        }),
+        (Some(("error", message)), _, _) => {
+            Err(read_err(io::Error::other(format!("broker: {message}"))))
+        }
+        _ => Err(read_err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unexpected broker reply {reply:?}"),
+        ))),
+    }
+}
+
+// ─── Helpers ─────────────────────────────────────────────────────────────────
+
+/// Parses `map ADDR LEN rw|ro`.
+fn parse_request(line: &str) -> Option<(u64, u64, bool)> {
+    match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["map", address, len, mode] => {
+            let writable = match *mode {
+                "rw" => true,
+                "ro" => false,
+                _ => return None,
+            };
+            Some((parse_num(address)?, parse_num(len)?, writable))
+        }
+        _ => None,
+    }
+}
+
+/// One reply line; `message` is flattened so the line stays one line.
+fn reply_line(kind: &str, message: &str) -> String {
+    format!("{kind} {}\n", message.replace(['\r', '\n'], " "))
+}
+
+/// Whether `path` is `/dev/mem`, by name or by device number (1:1).
+fn is_dev_mem(path: &Path) -> bool {
+    path == Path::new("/dev/mem")
+        || fs::metadata(path).is_ok_and(|meta| {
+            meta.file_type().is_char_device() && meta.rdev() == libc::makedev(1, 1)
+        })
+}
+
+/// Reads one line of at most [`MAX_LINE`] bytes, which must be complete by
+/// `deadline`. Bounded in size and total time, so a client cannot make the
+/// broker buffer without limit or hold a thread by sending a byte at a time.
+fn read_line(mut stream: &UnixStream, deadline: Instant) -> io::Result<String> {
+    let mut line = Vec::with_capacity(MAX_LINE);
+    let mut buf = [0u8; MAX_LINE];
+    while line.len() < MAX_LINE && !line.contains(&b'\n') {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "request not complete before the deadline",
+            ));
+        }
+        stream.set_read_timeout(Some(left))?;
+        let n = stream.read(&mut buf[..MAX_LINE - line.len()])?;
+        if n == 0 {
+            break;
+        }
+        line.extend_from_slice(&buf[..n]);
+    }
+    if let Some(end) = line.iter().position(|&b| b == b'\n') {
+        line.truncate(end + 1);
+    }
+    String::from_utf8(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn lookup_user(name: &str) -> Option<u32> {
+    if let Ok(uid) = name.parse() {
+        return Some(uid);
+    }
+    let name = CString::new(name).ok()?;
+    let mut buf = vec![0 as libc::c_char; 4096];
+    // SAFETY: all pointers are valid for the duration of the call.
+    unsafe {
+        let mut pwd: libc::passwd = mem::zeroed();
+        let mut result = ptr::null_mut();
+        libc::getpwnam_r(
+            name.as_ptr(),
+            &mut pwd,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut result,
+        );
+        (!result.is_null()).then_some(pwd.pw_uid)
+    }
+}
+
+fn lookup_group(name: &str) -> Option<u32> {
+    if let Ok(gid) = name.parse() {
+        return Some(gid);
+    }
+    let name = CString::new(name).ok()?;
+    let mut buf = vec![0 as libc::c_char; 4096];
+    // SAFETY: all pointers are valid for the duration of the call.
+    unsafe {
+        let mut grp: libc::group = mem::zeroed();
+        let mut result = ptr::null_mut();
+        libc::getgrnam_r(
+            name.as_ptr(),
+            &mut grp,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut result,
+        );
+        (!result.is_null()).then_some(grp.gr_gid)
+    }
+}
+
+/// Primary and supplementary groups of `uid`.
+fn groups_of(uid: u32, gid: u32) -> Vec<u32> {
+    let mut buf = vec![0 as libc::c_char; 4096];
+    // SAFETY: all pointers are valid for the duration of the calls, and
+    // `groups` has room for `count` entries.
+    unsafe {
+        let mut pwd: libc::passwd = mem::zeroed();
+        let mut result = ptr::null_mut();
+        libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result);
+        if result.is_null() {
+            return vec![gid];
+        }
+        let name = CStr::from_ptr(pwd.pw_name);
+        let mut count: libc::c_int = 32;
+        loop {
+            let mut groups = vec![0 as libc::gid_t; count as usize];
+            let wanted = count;
+            if libc::getgrouplist(name.as_ptr(), gid, groups.as_mut_ptr(), &mut count) >= 0 {
+                groups.truncate(count as usize);
+                return groups;
+            }
+            if count <= wanted {
+                return vec![gid];
+            }
+        }
+    }
+}
+
+fn peer_cred(stream: &UnixStream) -> io::Result<libc::ucred> {
+    // SAFETY: `cred` and `len` are valid for the duration of the call.
+    unsafe {
+        let mut cred: libc::ucred = mem::zeroed();
+        let mut len = mem::size_of::<libc::ucred>() as libc::socklen_t;
+        let rc = libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut _ as *mut libc::c_void,
+            &mut len,
+        );
+        if rc < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(cred)
+    }
+}
+
+/// Control buffer large enough for one descriptor, suitably aligned.
+#[repr(C, align(8))]
+struct FdControl([u8; 64]);
+
+fn send_with_fd(stream: &UnixStream, data: &[u8], fd: Option<RawFd>) -> io::Result<()> {
+    let mut control = FdControl([0; 64]);
+    // SAFETY: `msg` points at live buffers; the control buffer is large and
+    // aligned enough for one SCM_RIGHTS header and descriptor.
+    unsafe {
+        let mut iov = libc::iovec {
+            iov_base: data.as_ptr() as *mut libc::c_void,
+            iov_len: data.len(),
+        };
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        if let Some(fd) = fd {
+            let space = libc::CMSG_SPACE(mem::size_of::<RawFd>() as u32) as usize;
+            msg.msg_control = control.0.as_mut_ptr() as *mut libc::c_void;
+            msg.msg_controllen = space as _;
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_SOCKET;
+            (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<RawFd>() as u32) as _;
+            ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut RawFd, fd);
+        }
+        if libc::sendmsg(stream.as_raw_fd(), &msg, libc::MSG_NOSIGNAL) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+fn recv_with_fd(stream: &UnixStream) -> io::Result<(String, Option<OwnedFd>)> {
+    let mut data = [0u8; MAX_LINE];
+    let mut control = FdControl([0; 64]);
+    let mut fd = None;
+    // SAFETY: `msg` points at live buffers; received descriptors are taken
+    // into ownership exactly once.
+    let received = unsafe {
+        let mut iov = libc::iovec {
+            iov_base: data.as_mut_ptr() as *mut libc::c_void,
+            iov_len: data.len(),
+        };
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.0.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = control.0.len() as _;
+        let n = libc::recvmsg(stream.as_raw_fd(), &mut msg, libc::MSG_CMSG_CLOEXEC);
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+        while !cmsg.is_null() {
+            if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                let raw = ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const RawFd);
+                fd = Some(OwnedFd::from_raw_fd(raw));
+            }
+            cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+        }
+        n as usize
+    };
+    let reply = String::from_utf8_lossy(&data[..received]).into_owned();
+    Ok((reply, fd))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(device: &Path) -> Policy {
+        let uid = unsafe { libc::getuid() };
+        Policy::parse(&format!(
+            "device {}\nuser {uid} 0x1000 0x1000 rw\nuser {uid} 0x2000 0x1000 ro\n",
+            device.display()
+        ))
+        .unwrap()
+    }
+
+    /// A broker whose listener is never used; requests go through
+    /// [`Broker::handle`] on one end of a socket pair.
+    fn broker(name: &str, device: &Path) -> Broker {
+        let dir =
+            std::env::temp_dir().join(format!("ddevmem-broker-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let broker = Broker::bind(dir.join("sock"), policy(device)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        broker
+    }
+
+    /// Sends `request` (if any) and returns the broker's result and reply.
+    fn ask(broker: &Broker, request: Option<&str>) -> (Result<Grant, Error>, String, bool) {
+        let (mut client, server) = UnixStream::pair().unwrap();
+        if let Some(request) = request {
+            client.write_all(request.as_bytes()).unwrap();
+        }
+        let result = broker.handle(&server);
+        let (reply, fd) = recv_with_fd(&client).unwrap();
+        (result, reply, fd.is_some())
+    }
+
+    fn device(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("ddevmem-broker-{name}-{}.mem", std::process::id()));
+        fs::write(&path, vec![0u8; 0x3000]).unwrap();
+        path
+    }
+
+    #[test]
+    fn covered_requests_are_granted_with_a_descriptor() {
+        let mem = device("grant");
+        let broker = broker("grant", &mem);
+        let (result, reply, fd) = ask(&broker, Some("map 0x1000 0x100 rw\n"));
+        let grant = result.unwrap();
+        assert_eq!(
+            (grant.address, grant.len, grant.writable),
+            (0x1000, 0x100, true)
+        );
+        assert_eq!((reply.as_str(), fd), ("ok\n", true));
+
+        let (result, _, _) = ask(&broker, Some("map 0x2000 0x1000 ro\n"));
+        assert!(!result.unwrap().writable);
+        fs::remove_file(&mem).unwrap();
+    }
+
+    #[test]
+    fn uncovered_requests_are_denied() {
+        let mem = device("deny");
+        let broker = broker("deny", &mem);
+        for request in [
+            "map 0x2000 0x100 rw\n",
+            "map 0x1F00 0x200 ro\n",
+            "map 0x4000 0x10 ro\n",
+        ] {
+            let (result, reply, fd) = ask(&broker, Some(request));
+            assert!(matches!(result, Err(Error::Denied { .. })), "{request:?}");
+            assert!(reply.starts_with("denied uid "), "{reply:?}");
+            assert!(!fd);
+        }
+        fs::remove_file(&mem).unwrap();
+    }
+
+    #[test]
+    fn malformed_requests_are_denied() {
+        let mem = device("malformed");
+        let broker = broker("malformed", &mem);
+        for request in [
+            "hello\n",
+            "map 0x1000 0x100\n",
+            "map 0x1000 0x100 wx\n",
+            "map x 1 ro\n",
+        ] {
+            let (result, reply, _) = ask(&broker, Some(request));
+            assert!(matches!(
+                result,
+                Err(Error::Denied {
+                    address: 0,
+                    len: 0,
+                    ..
+                })
+            ));
+            assert!(reply.starts_with("denied malformed request"), "{reply:?}");
+        }
+        fs::remove_file(&mem).unwrap();
+    }
+
+    #[test]
+    fn silent_clients_time_out_with_an_error_reply() {
+        let mem = device("timeout");
+        let broker = broker("timeout", &mem).with_request_timeout(Duration::from_millis(50));
+        let (result, reply, fd) = ask(&broker, None);
+        assert!(matches!(result, Err(Error::CantReadFile { .. })));
+        assert!(reply.starts_with("error "), "{reply:?}");
+        assert!(!fd);
+        fs::remove_file(&mem).unwrap();
+    }
+
+    #[test]
+    fn trickling_clients_hit_the_request_deadline() {
+        let mem = device("trickle");
+        let broker = broker("trickle", &mem).with_request_timeout(Duration::from_millis(200));
+        let (mut client, server) = UnixStream::pair().unwrap();
+        let writer = thread::spawn(move || {
+            for byte in b"map 0x1000 0x100 ro\n" {
+                thread::sleep(Duration::from_millis(50));
+                if client.write_all(&[*byte]).is_err() {
+                    break;
+                }
+            }
+        });
+        let start = Instant::now();
+        let result = broker.handle(&server);
+        assert!(matches!(result, Err(Error::CantReadFile { .. })));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        drop(server);
+        writer.join().unwrap();
+        fs::remove_file(&mem).unwrap();
+    }
+
+    #[test]
+    fn clients_over_the_cap_are_turned_away() {
+        let mem = device("cap");
+        let dir = std::env::temp_dir().join(format!("ddevmem-broker-cap-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("sock");
+        let broker = Broker::bind(&socket, policy(&mem))
+            .unwrap()
+            .with_max_clients(2)
+            .with_request_timeout(Duration::from_secs(30));
+        thread::spawn(move || broker.serve());
+
+        let idle: Vec<_> = (0..2)
+            .map(|_| UnixStream::connect(&socket).unwrap())
+            .collect();
+        let busy = unsafe { map_read_only(&socket, 0x2000, 0x10) }.unwrap_err();
+        assert!(busy.to_string().contains("busy"), "{busy}");
+
+        // Closed idle clients free their slots.
+        drop(idle);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while unsafe { map_read_only(&socket, 0x2000, 0x10) }.is_err() {
+            assert!(Instant::now() < deadline, "slots were not freed");
+            thread::sleep(Duration::from_millis(10));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_file(&mem).unwrap();
+    }
+
+    #[test]
+    fn device_failures_are_reported_to_the_client() {
+        let mem = device("missing");
+        let broker = broker("missing", &mem);
+        fs::remove_file(&mem).unwrap();
+        let (result, reply, _) = ask(&broker, Some("map 0x1000 0x100 ro\n"));
+        assert!(matches!(result, Err(Error::CantOpenFile { .. })));
+        assert!(reply.starts_with("error ") && reply.ends_with('\n') && reply.lines().count() == 1);
+    }
+
+    #[test]
+    fn dev_mem_is_refused_as_the_device() {
+        let uid = unsafe { libc::getuid() };
+        for (text, msg) in [
+            (
+                format!("user {uid} 0x0 0x1000 ro\n"),
+                "missing `device PATH` line",
+            ),
+            (
+                format!("device /tmp/x\n\ndevice /dev/mem\nuser {uid} 0x0 0x1000 ro\n"),
+                "line 3: /dev/mem exposes all of physical memory",
+            ),
+        ] {
+            let err = Policy::parse(&text).unwrap_err();
+            assert!(err.to_string().starts_with(msg), "{err}");
+        }
+        assert!(Policy::parse(&format!("device /tmp/x\nuser {uid} 0x0 0x10 rw\n")).is_ok());
+    }
+
+    #[test]
+    fn malformed_policy_lines_are_named() {
+        for (text, msg) in [
+            (
+                "device /tmp/x\nuser 0 0x0 0x10 wx\n",
+                "line 2: mode must be",
+            ),
+            ("\n# comment\ngroup 0 zz 0x10 ro\n", "line 3: bad address"),
+            ("user 0 0x0\n", "line 1: expected"),
+        ] {
+            let err = Policy::parse(text).unwrap_err();
+            assert!(err.to_string().starts_with(msg), "{err}");
+        }
+    }
+
+    #[test]
+    fn rules_must_cover_the_whole_range() {
+        let policy =
+            Policy::parse("device /tmp/x\nuser 7 0x1000 0x100 rw\ngroup 9 0x2000 0x100 ro\n")
+                .unwrap();
+        assert!(policy.allows(7, &[], 0x1000, 0x100, true));
+        assert!(!policy.allows(7, &[], 0x10FF, 0x2, false));
+        assert!(!policy.allows(8, &[], 0x1000, 0x10, false));
+        assert!(policy.allows(8, &[9], 0x2000, 0x10, false));
+        assert!(!policy.allows(8, &[9], 0x2000, 0x10, true));
+        assert!(!policy.allows(7, &[], u64::MAX, 2, false));
+    }
+}
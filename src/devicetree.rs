@@ -0,0 +1,488 @@
+//! Device-tree base address lookup.
+//!
+//! The kernel exposes the flattened device tree under `/proc/device-tree`:
+//! every node is a directory and every property a file holding its raw,
+//! big-endian value. [`DeviceTree`] finds nodes there by `compatible`
+//! string or label and decodes their `reg` property into CPU physical
+//! `(address, size)` pairs, applying the parent's `#address-cells` /
+//! `#size-cells` and every `ranges` translation up to the root. The pairs
+//! feed straight into [`DevMem::new`](crate::DevMem::new):
+//!
+//! ```rust,no_run
+//! use ddevmem::{devicetree::DeviceTree, DevMem};
+//!
+//! let dt = DeviceTree::system();
+//! let timer = &dt.find_compatible("xlnx,xps-timer-1.00.a").unwrap()[0];
+//! let (address, size) = timer.reg().unwrap()[0];
+//! let devmem = unsafe { DevMem::new(address, Some(size)).unwrap() };
+//! ```
+//!
+//! The root is configurable: [`DeviceTree::open`] takes any directory laid
+//! out the same way, so a fixture can stand in for the real tree in tests.
+//!
+//! Address values wider than 64 bits (e.g. the flag cell of PCI addresses)
+//! are rejected rather than truncated.
+
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use crate::Error;
+
+/// Where the kernel exposes the live device tree.
+pub const PROC_DEVICE_TREE: &str = "/proc/device-tree";
+
+/// A device tree laid out as directories and property files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceTree {
+    root: PathBuf,
+}
+
+impl DeviceTree {
+    /// The live tree under [`PROC_DEVICE_TREE`].
+    pub fn system() -> Self {
+        Self::open(PROC_DEVICE_TREE)
+    }
+
+    /// A tree rooted at `root`, e.g. a fixture directory.
+    pub fn open(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Root directory of the tree.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Node at the device-tree path `path` (e.g. `/amba/serial@e0000000`).
+    /// The node is not checked for existence.
+    pub fn node(&self, path: &str) -> Node {
+        let rel = path.trim_start_matches('/');
+        Node {
+            root: self.root.clone(),
+            rel: PathBuf::from(rel),
+        }
+    }
+
+    /// All nodes whose `compatible` list contains `compatible`, in
+    /// directory order (sorted by name at each level).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantReadFile`] if a directory or `compatible`
+    /// property cannot be read.
+    pub fn find_compatible(&self, compatible: &str) -> Result<Vec<Node>, Error> {
+        let mut found = Vec::new();
+        self.walk(&self.node("/"), &mut |node| {
+            if node.compatible()?.iter().any(|c| c == compatible) {
+                found.push(node.clone());
+            }
+            Ok(())
+        })?;
+        Ok(found)
+    }
+
+    /// Node carrying `label`: looked up in `/__symbols__` (present when the
+    /// tree was compiled with `dtc -@`), then as a `label` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantReadFile`] if the tree cannot be read.
+    pub fn find_label(&self, label: &str) -> Result<Option<Node>, Error> {
+        let symbols = self.node("/__symbols__");
+        if let Some(path) = symbols.string_property(label)? {
+            return Ok(Some(self.node(&path)));
+        }
+
+        let mut found = None;
+        self.walk(&self.node("/"), &mut |node| {
+            if found.is_none() && node.string_property("label")?.as_deref() == Some(label) {
+                found = Some(node.clone());
+            }
+            Ok(())
+        })?;
+        Ok(found)
+    }
+
+    fn walk(
+        &self,
+        node: &Node,
+        visit: &mut dyn FnMut(&Node) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        visit(node)?;
+        let dir = node.dir();
+        let err = |source| Error::CantReadFile {
+            path: dir.clone(),
+            source,
+        };
+        let mut children = Vec::new();
+        for entry in fs::read_dir(&dir).map_err(err)? {
+            let entry = entry.map_err(err)?;
+            if entry.file_type().map_err(err)?.is_dir() {
+                children.push(entry.file_name());
+            }
+        }
+        children.sort();
+        for name in children {
+            if name == "__symbols__" || name == "__fixups__" || name == "__local_fixups__" {
+                continue;
+            }
+            self.walk(
+                &Node {
+                    root: self.root.clone(),
+                    rel: node.rel.join(name),
+                },
+                visit,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A node of a [`DeviceTree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    root: PathBuf,
+    /// Path relative to the root; empty for the root itself.
+    rel: PathBuf,
+}
+
+impl Node {
+    /// Device-tree path, e.g. `/amba/serial@e0000000`.
+    pub fn path(&self) -> String {
+        format!("/{}", self.rel.display())
+    }
+
+    /// Node name including the unit address, e.g. `serial@e0000000`.
+    pub fn name(&self) -> String {
+        self.rel
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Parent node, `None` for the root.
+    pub fn parent(&self) -> Option<Node> {
+        let parent = self.rel.parent()?;
+        Some(Node {
+            root: self.root.clone(),
+            rel: parent.to_owned(),
+        })
+    }
+
+    /// Raw value of property `name`, `None` if absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantReadFile`] if the property exists but cannot be
+    /// read.
+    pub fn property(&self, name: &str) -> Result<Option<Vec<u8>>, Error> {
+        let path = self.dir().join(name);
+        match fs::read(&path) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(Error::CantReadFile { path, source }),
+        }
+    }
+
+    /// Entries of the `compatible` string list, most specific first.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`property`](Self::property).
+    pub fn compatible(&self) -> Result<Vec<String>, Error> {
+        Ok(self
+            .property("compatible")?
+            .map(|raw| {
+                raw.split(|&b| b == 0)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| String::from_utf8_lossy(s).into_owned())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// `reg` decoded into CPU physical `(address, size)` pairs.
+    ///
+    /// The cell counts come from the parent node (defaults 2 and 1), and
+    /// each address is translated through the `ranges` of every ancestor
+    /// bus. An ancestor without `ranges` is not memory-mapped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantReadFile`] if a property cannot be read, the
+    /// node has no `reg`, or an address cannot be translated.
+    pub fn reg(&self) -> Result<Vec<(usize, usize)>, Error> {
+        let invalid = |msg: String| Error::CantReadFile {
+            path: self.dir().join("reg"),
+            source: io::Error::new(ErrorKind::InvalidData, msg),
+        };
+        let parent = self
+            .parent()
+            .ok_or_else(|| invalid("the root has no `reg`".into()))?;
+        let address_cells = parent.cells("#address-cells", 2)?;
+        let size_cells = parent.cells("#size-cells", 1)?;
+        let raw = self
+            .property("reg")?
+            .ok_or_else(|| invalid("missing `reg`".into()))?;
+
+        let entry = (address_cells + size_cells) * 4;
+        if entry == 0 || raw.len() % entry != 0 {
+            return Err(invalid(format!("`reg` is not a multiple of {entry} bytes")));
+        }
+        raw.chunks(entry)
+            .map(|chunk| {
+                let (address, size) = chunk.split_at(address_cells * 4);
+                let address = parent.translate(to_u64(address).map_err(&invalid)?)?;
+                let size = to_u64(size).map_err(&invalid)?;
+                let address = usize::try_from(address)
+                    .map_err(|_| invalid(format!("address {address:#X} exceeds usize")))?;
+                let size = usize::try_from(size)
+                    .map_err(|_| invalid(format!("size {size:#X} exceeds usize")))?;
+                Ok((address, size))
+            })
+            .collect()
+    }
+
+    /// Translates `address`, expressed in this node's child address space,
+    /// into the CPU physical address space.
+    fn translate(&self, mut address: u64) -> Result<u64, Error> {
+        let mut bus = self.clone();
+        while let Some(parent) = bus.parent() {
+            let path = bus.dir().join("ranges");
+            let invalid = |msg: String| Error::CantReadFile {
+                path: path.clone(),
+                source: io::Error::new(ErrorKind::InvalidData, msg),
+            };
+            let Some(ranges) = bus.property("ranges")? else {
+                return Err(invalid(format!("{} has no `ranges`", bus.path())));
+            };
+            if !ranges.is_empty() {
+                let child_cells = bus.cells("#address-cells", 2)?;
+                let parent_cells = parent.cells("#address-cells", 2)?;
+                let size_cells = bus.cells("#size-cells", 1)?;
+                let entry = (child_cells + parent_cells + size_cells) * 4;
+                if entry == 0 || ranges.len() % entry != 0 {
+                    return Err(invalid(format!(
+                        "`ranges` is not a multiple of {entry} bytes"
+                    )));
+                }
+                let mut mapped = None;
+                for chunk in ranges.chunks(entry) {
+                    let (child, rest) = chunk.split_at(child_cells * 4);
+                    let (parent_base, size) = rest.split_at(parent_cells * 4);
+                    let child = to_u64(child).map_err(&invalid)?;
+                    let size = to_u64(size).map_err(&invalid)?;
+                    if address >= child && address - child < size {
+                        let parent_base = to_u64(parent_base).map_err(&invalid)?;
+                        let translated =
+                            parent_base.checked_add(address - child).ok_or_else(|| {
+                                invalid(format!(
+                                    "{address:#X} translated through the `ranges` of {} \
+                                     exceeds 64 bits",
+                                    bus.path()
+                                ))
+                            })?;
+                        mapped = Some(translated);
+                        break;
+                    }
+                }
+                address = mapped.ok_or_else(|| {
+                    invalid(format!(
+                        "{address:#X} is outside the `ranges` of {}",
+                        bus.path()
+                    ))
+                })?;
+            }
+            bus = parent;
+        }
+        Ok(address)
+    }
+
+    /// A one-cell property such as `#address-cells`, or `default`.
+    fn cells(&self, name: &str, default: usize) -> Result<usize, Error> {
+        match self.property(name)? {
+            None => Ok(default),
+            Some(raw) => Ok(to_u64(&raw).map_err(|msg| Error::CantReadFile {
+                path: self.dir().join(name),
+                source: io::Error::new(ErrorKind::InvalidData, msg),
+            })? as usize),
+        }
+    }
+
+    /// A NUL-terminated string property.
+    fn string_property(&self, name: &str) -> Result<Option<String>, Error> {
+        Ok(self.property(name)?.map(|raw| {
+            let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+            String::from_utf8_lossy(&raw[..end]).into_owned()
+        }))
+    }
+
+    fn dir(&self) -> PathBuf {
+        self.root.join(&self.rel)
+    }
+}
+
+/// Joins big-endian cells into one value.
+fn to_u64(cells: &[u8]) -> Result<u64, String> {
+    let (high, low) = cells.split_at(cells.len().saturating_sub(8));
+    if high.iter().any(|&b| b != 0) {
+        return Err(format!(
+            "value of {} cells exceeds 64 bits",
+            cells.len() / 4
+        ));
+    }
+    Ok(low.iter().fold(0, |acc, &b| acc << 8 | b as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|c| c.to_be_bytes()).collect()
+    }
+
+    /// Fixture tree:
+    ///
+    /// ```text
+    /// / { #address-cells = <1>; #size-cells = <1>;
+    ///     bus@80000000 { #address-cells = <1>; #size-cells = <1>;
+    ///                    ranges = <0x0 0x80000000 0x100000>;
+    ///                    timer@4000 { compatible = "acme,timer";
+    ///                                 reg = <0x4000 0x100>; }; }; };
+    /// ```
+    fn fixture(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("ddevmem-devicetree-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let bus = root.join("bus@80000000");
+        let timer = bus.join("timer@4000");
+        fs::create_dir_all(&timer).unwrap();
+        fs::write(root.join("#address-cells"), cells(&[1])).unwrap();
+        fs::write(root.join("#size-cells"), cells(&[1])).unwrap();
+        fs::write(bus.join("#address-cells"), cells(&[1])).unwrap();
+        fs::write(bus.join("#size-cells"), cells(&[1])).unwrap();
+        fs::write(bus.join("ranges"), cells(&[0x0, 0x8000_0000, 0x10_0000])).unwrap();
+        fs::write(timer.join("compatible"), b"acme,timer\0").unwrap();
+        fs::write(timer.join("reg"), cells(&[0x4000, 0x100])).unwrap();
+        fs::create_dir_all(root.join("__symbols__")).unwrap();
+        fs::write(
+            root.join("__symbols__/timer0"),
+            b"/bus@80000000/timer@4000\0",
+        )
+        .unwrap();
+        root
+    }
+
+    fn reg_error(dt: &DeviceTree) -> String {
+        match dt.node("/bus@80000000/timer@4000").reg() {
+            Err(Error::CantReadFile { source, .. }) => source.to_string(),
+            other => panic!("expected CantReadFile, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn reg_is_translated_through_ranges() {
+        let root = fixture("ranges");
+        let dt = DeviceTree::open(&root);
+        let nodes = dt.find_compatible("acme,timer").unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].path(), "/bus@80000000/timer@4000");
+        assert_eq!(nodes[0].compatible().unwrap(), ["acme,timer"]);
+        assert_eq!(nodes[0].reg().unwrap(), [(0x8000_4000, 0x100)]);
+        assert!(dt.find_compatible("simple-bus").unwrap().is_empty());
+
+        let by_label = dt.find_label("timer0").unwrap().unwrap();
+        assert_eq!(by_label, nodes[0]);
+        assert!(dt.find_label("timer1").unwrap().is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn empty_ranges_is_an_identity_mapping() {
+        let root = fixture("empty-ranges");
+        fs::write(root.join("bus@80000000/ranges"), b"").unwrap();
+        let dt = DeviceTree::open(&root);
+        assert_eq!(
+            dt.node("/bus@80000000/timer@4000").reg().unwrap(),
+            [(0x4000, 0x100)]
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn address_outside_ranges_is_rejected() {
+        let root = fixture("ranges-miss");
+        let bus = root.join("bus@80000000");
+        fs::write(bus.join("ranges"), cells(&[0x0, 0x8000_0000, 0x1000])).unwrap();
+        let dt = DeviceTree::open(&root);
+        let err = reg_error(&dt);
+        assert!(err.contains("0x4000 is outside the `ranges`"), "{err}");
+
+        fs::remove_file(bus.join("ranges")).unwrap();
+        let err = reg_error(&dt);
+        assert!(err.contains("has no `ranges`"), "{err}");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn translation_past_64_bits_is_rejected() {
+        let root = fixture("ranges-overflow");
+        fs::write(root.join("#address-cells"), cells(&[2])).unwrap();
+        fs::write(
+            root.join("bus@80000000/ranges"),
+            cells(&[0x0, 0xFFFF_FFFF, 0xFFFF_F000, 0x10_0000]),
+        )
+        .unwrap();
+        let err = reg_error(&DeviceTree::open(&root));
+        assert!(
+            err.contains("0x4000 translated through the `ranges`"),
+            "{err}"
+        );
+        assert!(err.ends_with("exceeds 64 bits"), "{err}");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn cells_wider_than_64_bits_are_rejected() {
+        let root = fixture("wide-cells");
+        let bus = root.join("bus@80000000");
+        let timer = bus.join("timer@4000");
+        fs::write(bus.join("#address-cells"), cells(&[3])).unwrap();
+        fs::write(
+            bus.join("ranges"),
+            cells(&[0, 0, 0x0, 0x8000_0000, 0x10_0000]),
+        )
+        .unwrap();
+
+        // Three cells whose top cell is zero still fit.
+        fs::write(timer.join("reg"), cells(&[0, 0, 0x4000, 0x100])).unwrap();
+        let dt = DeviceTree::open(&root);
+        assert_eq!(
+            dt.node("/bus@80000000/timer@4000").reg().unwrap(),
+            [(0x8000_4000, 0x100)]
+        );
+
+        // A flag in the top cell (as in PCI addresses) would be truncated.
+        fs::write(timer.join("reg"), cells(&[0x0200_0000, 0, 0x4000, 0x100])).unwrap();
+        let err = reg_error(&dt);
+        assert!(err.contains("value of 3 cells exceeds 64 bits"), "{err}");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn reg_with_a_partial_entry_is_rejected() {
+        let root = fixture("partial-reg");
+        fs::write(
+            root.join("bus@80000000/timer@4000/reg"),
+            cells(&[0x4000, 0x100, 0x5000]),
+        )
+        .unwrap();
+        let err = reg_error(&DeviceTree::open(&root));
+        assert!(err.contains("not a multiple of 8 bytes"), "{err}");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,30 +1,191 @@
 use bytemuck::{AnyBitPattern, NoUninit};
-use std::{cell::UnsafeCell, fmt, io::Error as IOError};
+#[cfg(feature = "device")]
+use std::path::Path;
+use std::{
+    fmt,
+    io::{Error as IOError, ErrorKind},
+    mem::MaybeUninit,
+    path::PathBuf,
+    sync::Arc,
+};
 
-#[cfg(all(feature = "device", not(feature = "emulator")))]
-use memmap2::{MmapMut, MmapOptions};
+use crate::backend::{Backend, DefaultBackend, Word};
+use crate::region::{Region, Registry};
+#[cfg(feature = "device")]
+use crate::{backend::MapOptions, policy::AddressPolicy};
 
-#[cfg(all(feature = "device", not(feature = "emulator")))]
-use std::fs::OpenOptions;
-
-/// Error returned when creating a [`DevMem`] instance.
+/// Error returned when creating, claiming or accessing a [`DevMem`] region.
 ///
-/// Wraps the underlying I/O error from opening or memory-mapping `/dev/mem`.
+/// The file variants wrap the underlying I/O error from opening or
+/// memory-mapping the device file (`/dev/mem` unless another path was given
+/// to [`DevMemBuilder`]). The remaining variants describe rejected accesses
+/// (the `try_*` methods), register maps that do not fit their region,
+/// ranges forbidden by an address policy, and overlapping claims.
 /// Implements [`std::fmt::Display`], [`std::error::Error`], and
 /// [`From<Error>`](std::convert::From) for [`std::io::Error`].
 #[derive(Debug)]
 pub enum Error {
-    /// The `/dev/mem` file could not be opened.
-    CantOpenFile(IOError),
+    /// The device file could not be opened.
+    CantOpenFile {
+        /// Path that was opened.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: IOError,
+    },
     /// The memory-mapping (`mmap`) call failed.
-    CantMmapFile(IOError),
+    CantMmapFile {
+        /// Path that was mapped.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: IOError,
+    },
+    /// A descriptive file (e.g. a sysfs attribute) could not be read or
+    /// parsed.
+    CantReadFile {
+        /// Path that was read.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: IOError,
+    },
+    /// An access or claim does not fit inside the region.
+    OutOfBounds {
+        /// Requested offset.
+        offset: usize,
+        /// Number of bytes requested.
+        size: usize,
+        /// Length of the region.
+        len: usize,
+    },
+    /// An access offset is not a multiple of the alignment of its type.
+    Misaligned {
+        /// Requested offset.
+        offset: usize,
+        /// Required alignment in bytes.
+        align: usize,
+    },
+    /// A register of a [`register_map!`](crate::register_map) does not fit
+    /// inside the region passed to its constructor.
+    RegisterOutOfBounds {
+        /// Name of the offending register.
+        register: &'static str,
+        /// Offset of the register.
+        offset: usize,
+        /// Bytes spanned by the register (all elements for arrays).
+        size: usize,
+        /// Length of the region.
+        len: usize,
+    },
+    /// A write was attempted on a read-only mapping (see
+    /// [`DevMemBuilder::read_only`]).
+    ReadOnly {
+        /// Offset of the rejected write.
+        offset: usize,
+    },
+    /// An access raised SIGBUS / SIGSEGV on a fault-tolerant `DevMem`
+    /// (see [`DevMem::with_fault_tolerance`]).
+    BusFault {
+        /// Offset of the faulting byte.
+        offset: usize,
+    },
+    /// The physical range is not permitted by the
+    /// [`AddressPolicy`](crate::policy::AddressPolicy) in effect; nothing
+    /// was mapped.
+    AddressForbidden {
+        /// Physical address requested.
+        address: usize,
+        /// Length requested.
+        len: usize,
+    },
+    /// A claim overlaps another live claim on the same mapping.
+    RegionClaimed {
+        /// Name the rejected claim was requested under.
+        owner: &'static str,
+        /// Physical address of the rejected claim.
+        address: usize,
+        /// Length of the rejected claim.
+        len: usize,
+        /// Owner of the live claim in the way.
+        holder: &'static str,
+        /// Physical address of the live claim.
+        holder_address: usize,
+        /// Length of the live claim.
+        holder_len: usize,
+    },
+    /// The region broker refused a request (see [`broker`](crate::broker)).
+    Denied {
+        /// Physical address requested.
+        address: usize,
+        /// Length requested.
+        len: usize,
+        /// Reason given by the broker.
+        reason: String,
+    },
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Error::CantOpenFile(err) => write!(f, "failed to open /dev/mem: {err}"),
-            Error::CantMmapFile(err) => write!(f, "failed to mmap /dev/mem: {err}"),
+            Error::CantOpenFile { path, source } => {
+                write!(f, "failed to open {}: {source}", path.display())
+            }
+            Error::CantMmapFile { path, source } => {
+                write!(f, "failed to mmap {}: {source}", path.display())
+            }
+            Error::CantReadFile { path, source } => {
+                write!(f, "failed to read {}: {source}", path.display())
+            }
+            Error::OutOfBounds { offset, size, len } => write!(
+                f,
+                "range {offset:#X}..{:#X} exceeds region of {len:#X} bytes",
+                offset.saturating_add(*size)
+            ),
+            Error::Misaligned { offset, align } => {
+                write!(f, "offset {offset:#X} is not aligned to {align} bytes")
+            }
+            Error::RegisterOutOfBounds {
+                register,
+                offset,
+                size,
+                len,
+            } => write!(
+                f,
+                "register `{register}` at {offset:#X}..{:#X} exceeds region of {len:#X} bytes",
+                offset + size
+            ),
+            Error::ReadOnly { offset } => {
+                write!(f, "write at offset {offset:#X} to a read-only mapping")
+            }
+            Error::BusFault { offset } => write!(f, "bus fault at offset {offset:#X}"),
+            Error::AddressForbidden { address, len } => write!(
+                f,
+                "physical range {address:#X}..{:#X} is not permitted by the address policy",
+                address.saturating_add(*len)
+            ),
+            Error::RegionClaimed {
+                owner,
+                address,
+                len,
+                holder,
+                holder_address,
+                holder_len,
+            } => {
+                write!(
+                    f,
+                    "cannot claim {address:#X}..{:#X} for {owner}: \
+                     overlaps {holder} at {holder_address:#X}..{:#X}",
+                    address + len,
+                    holder_address + holder_len
+                )
+            }
+            Error::Denied {
+                address,
+                len,
+                reason,
+            } => write!(
+                f,
+                "broker denied {address:#X}..{:#X}: {reason}",
+                address.saturating_add(*len)
+            ),
         }
     }
 }
@@ -32,7 +193,17 @@ impl fmt::Display for Error {
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Error::CantOpenFile(err) | Error::CantMmapFile(err) => Some(err),
+            Error::CantOpenFile { source, .. }
+            | Error::CantMmapFile { source, .. }
+            | Error::CantReadFile { source, .. } => Some(source),
+            Error::OutOfBounds { .. }
+            | Error::Misaligned { .. }
+            | Error::RegisterOutOfBounds { .. }
+            | Error::ReadOnly { .. }
+            | Error::BusFault { .. }
+            | Error::AddressForbidden { .. }
+            | Error::RegionClaimed { .. }
+            | Error::Denied { .. } => None,
         }
     }
 }
@@ -40,47 +211,102 @@ impl std::error::Error for Error {
 impl From<Error> for IOError {
     fn from(err: Error) -> IOError {
         match err {
-            Error::CantOpenFile(e) | Error::CantMmapFile(e) => e,
+            Error::CantOpenFile { source, .. }
+            | Error::CantMmapFile { source, .. }
+            | Error::CantReadFile { source, .. } => source,
+            Error::OutOfBounds { .. }
+            | Error::Misaligned { .. }
+            | Error::RegisterOutOfBounds { .. } => {
+                IOError::new(ErrorKind::InvalidInput, err.to_string())
+            }
+            Error::ReadOnly { .. } | Error::AddressForbidden { .. } => {
+                IOError::new(ErrorKind::PermissionDenied, err.to_string())
+            }
+            Error::BusFault { .. } => IOError::other(err.to_string()),
+            Error::RegionClaimed { .. } => IOError::new(ErrorKind::AddrInUse, err.to_string()),
+            Error::Denied { .. } => IOError::new(ErrorKind::PermissionDenied, err.to_string()),
         }
     }
 }
 
 /// A memory-mapped view of a physical address range obtained from `/dev/mem`.
 ///
-/// All reads and writes go through [`std::ptr::read_volatile`] /
-/// [`std::ptr::write_volatile`], making this type suitable for MMIO register
-/// access where the compiler must not reorder, merge, or elide accesses.
+/// All reads and writes go through the region's [`Backend`], which performs
+/// volatile accesses suitable for MMIO register access where the compiler
+/// must not reorder, merge, or elide accesses.
 ///
 /// # Backends
 ///
-/// * **`device`** (default) — opens `/dev/mem` with `memmap2`.
-/// * **`emulator`** — uses a heap-allocated `Vec<u8>` for testing.
+/// * **`device`** (default) — [`Mmap`](crate::backend::Mmap), opens
+///   `/dev/mem` with `memmap2`.
+/// * **`emulator`** — [`Emulator`](crate::backend::Emulator), a heap buffer
+///   for testing.
+///
+/// [`DevMem::new`] uses [`DefaultBackend`]; any other backend, including a
+/// custom one, can be plugged in with [`DevMem::from_backend`].
+///
+/// # Views
+///
+/// [`DevMem::slice`] carves a sub-region out of an existing `DevMem`. The
+/// view shares the backend (and thus the mapping) through an [`Arc`], so
+/// several register maps can live at different offsets of one large window.
+///
+/// # Alignment
 ///
-/// Enable exactly one of the two.
+/// Typed accesses are issued at the natural width of `T`. Offsets that are
+/// not a multiple of `align_of::<T>()` are rejected by default; see
+/// [`Alignment`] and [`DevMem::read_unaligned`].
 ///
 /// # Thread safety
 ///
 /// `DevMem` is `Send + Sync` but provides no internal synchronization.
-/// Wrap it in an [`Arc`](std::sync::Arc) and protect all register accesses
+/// Wrap it in an [`Arc`] and protect all register accesses
 /// with a lock (e.g. `tokio::sync::Mutex`) when sharing across threads.
-pub struct DevMem {
-    /// `UnsafeCell` is required for interior mutability: volatile writes go
-    /// through `&self` (necessary when `DevMem` is shared via `Arc`), and the
-    /// Rust memory model requires `UnsafeCell` to permit mutation through a
-    /// shared reference without invoking undefined behaviour.
-    #[cfg(feature = "emulator")]
-    mmap: UnsafeCell<Vec<u8>>,
-    #[cfg(all(feature = "device", not(feature = "emulator")))]
-    mmap: UnsafeCell<MmapMut>,
+pub struct DevMem<B: Backend = DefaultBackend> {
+    backend: Arc<B>,
+    /// Claims made through this mapping, shared with every slice.
+    claims: Registry,
+    /// Offset of this view within `backend`.
+    start: usize,
+    len: usize,
     address: usize,
+    alignment: Alignment,
+    fault_tolerant: bool,
 }
 
-// SAFETY: Volatile MMIO accesses are inherently thread-unsafe at the hardware
-// level. We declare Send + Sync here and delegate synchronization responsibility
-// to the caller (Arc<Mutex<RegisterMap>> is the recommended pattern). This
-// mirrors how AtomicXxx types work: they use UnsafeCell and assert Sync.
-unsafe impl Send for DevMem {}
-unsafe impl Sync for DevMem {}
+/// How typed [`DevMem`] accesses treat offsets that are not a multiple of
+/// `align_of::<T>()`.
+///
+/// A misaligned volatile access is undefined behaviour in Rust and a bus
+/// fault on many ARM cores, so it is never issued as-is. Alignment is judged
+/// on the physical address `address() + offset`.
+///
+/// The unchecked accessors used by [`register_map!`](crate::register_map)
+/// bypass the policy; debug builds assert their alignment instead.
+///
+/// ```rust
+/// use ddevmem::{Alignment, DevMem};
+///
+/// let strict = unsafe { DevMem::new(0x4000_0000, Some(0x10)).unwrap() };
+/// assert_eq!(strict.write(0x01, 0x1234_5678u32), None);
+/// assert_eq!(strict.read::<u16>(0x03), None);
+/// assert_eq!(strict.read::<u64>(0x04), None);
+/// assert_eq!(strict.read::<u64>(0x08), Some(0));
+///
+/// let split = strict.slice(0, 0x10).unwrap().with_alignment(Alignment::Split);
+/// split.write(0x01, 0x1234_5678u32).unwrap(); // four byte writes
+/// assert_eq!(split.read::<u32>(0x01), Some(0x1234_5678));
+/// assert_eq!(strict.read::<u8>(0x01), Some(0x78));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    /// Reject the access with [`Error::Misaligned`] (or `None`).
+    #[default]
+    Strict,
+    /// Perform the access as a run of single-byte bus accesses, like
+    /// [`DevMem::read_unaligned`].
+    Split,
+}
 
 impl DevMem {
     /// Opens and memory-maps a physical address range.
@@ -90,8 +316,10 @@ impl DevMem {
     ///
     /// # Arguments
     ///
-    /// * `address` — physical base address (must be page-aligned for
-    ///   `/dev/mem`).
+    /// * `address` — physical base address. It does not need to be
+    ///   page-aligned: the enclosing pages are mapped, and offsets,
+    ///   [`address`](Self::address), `len()` and `as_ptr()` all refer to
+    ///   `address` itself.
     /// * `size` — length in bytes.  `None` defaults to the system page
     ///   size.
     ///
@@ -104,37 +332,449 @@ impl DevMem {
     ///
     /// # Errors
     ///
-    /// Returns [`Error::CantOpenFile`] if `/dev/mem` cannot be opened, or
+    /// Returns [`Error::AddressForbidden`] if a global
+    /// [`AddressPolicy`](crate::policy::AddressPolicy) rejects the range,
+    /// [`Error::CantOpenFile`] if `/dev/mem` cannot be opened, or
     /// [`Error::CantMmapFile`] if the `mmap` call fails.
     pub unsafe fn new(address: usize, size: Option<usize>) -> Result<Self, Error> {
-        let page_size = page_size::get();
-        let size = size.unwrap_or(page_size);
-
-        #[cfg(all(feature = "device", not(feature = "emulator")))]
-        {
-            let file = OpenOptions::new()
-                .read(true)
-                .write(true)
-                .create(false)
-                .open("/dev/mem")
-                .map_err(Error::CantOpenFile)?;
-
-            let mmap = MmapOptions::new()
-                .len(size)
-                .offset(address as u64)
-                .map_mut(&file)
-                .map_err(Error::CantMmapFile)?;
-
-            Ok(Self { mmap: UnsafeCell::new(mmap), address })
+        let size = size.unwrap_or_else(page_size::get);
+
+        #[cfg(all(
+            any(
+                feature = "default-device",
+                all(feature = "device", not(feature = "emulator"))
+            ),
+            not(feature = "default-emulator")
+        ))]
+        let backend = {
+            crate::policy::check_global(address, size)?;
+            crate::iomem::warn_if_ram(address, size);
+            crate::backend::Mmap::open(address, size)?
+        };
+
+        #[cfg(any(
+            feature = "default-emulator",
+            all(feature = "emulator", not(feature = "device"))
+        ))]
+        let backend = crate::backend::Emulator::new(size);
+
+        Ok(Self::from_backend(address, backend))
+    }
+}
+
+/// Builder for a [`DevMem`] backed by any mmap-able file.
+///
+/// `/dev/mem` is only one way to reach MMIO: the Linux UIO framework exposes
+/// `/dev/uioN`, PCI devices expose `resourceN` files under sysfs, and some
+/// FPGA managers provide their own nodes. All of them accept the same shared
+/// `mmap`.
+///
+/// | Setting     | Default                                              |
+/// |-------------|------------------------------------------------------|
+/// | [`path`]    | `/dev/mem`                                           |
+/// | [`offset`]  | `0` — file offset, any alignment                     |
+/// | [`len`]     | rest of the file, or one page if the file size is 0  |
+/// | [`address`] | `offset` — what [`DevMem::address`] reports          |
+///
+/// The way the file is opened and mapped is controlled by
+/// [`sync`](DevMemBuilder::sync), [`read_only`](DevMemBuilder::read_only),
+/// [`populate`](DevMemBuilder::populate) and
+/// [`locked`](DevMemBuilder::locked) (all off by default; see
+/// [`MapOptions`]). The resulting `DevMem` shows them in its `Debug`
+/// output.
+///
+/// ```rust,no_run
+/// use ddevmem::DevMem;
+///
+/// // BAR0 of a PCI device; the physical address is only informational.
+/// let bar0 = unsafe {
+///     DevMem::builder()
+///         .path("/sys/bus/pci/devices/0000:01:00.0/resource0")
+///         .address(0xF000_0000)
+///         .build()
+///         .unwrap()
+/// };
+/// println!("{bar0:?}");
+/// ```
+///
+/// A regular file works as well, which is handy in tests:
+///
+/// ```rust
+/// use ddevmem::DevMem;
+///
+/// let path = std::env::temp_dir().join("ddevmem-builder-doctest.bin");
+/// std::fs::write(&path, [0u8; 64]).unwrap();
+///
+/// let devmem = unsafe { DevMem::builder().path(&path).build().unwrap() };
+/// assert_eq!(devmem.len(), 64);
+/// devmem.write(0x10, 0xDEAD_BEEFu32).unwrap();
+/// drop(devmem);
+///
+/// let bytes = std::fs::read(&path).unwrap();
+/// assert_eq!(&bytes[0x10..0x14], &0xDEAD_BEEFu32.to_ne_bytes());
+///
+/// // Offsets need not be page-aligned: offset 0 is the requested byte.
+/// let reg = unsafe { DevMem::builder().path(&path).offset(0x10).len(4).build().unwrap() };
+/// assert_eq!(reg.address(), 0x10);
+/// assert_eq!(reg.len(), 4);
+/// assert_eq!(reg.read::<u32>(0), Some(0xDEAD_BEEF));
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+///
+/// [`path`]: DevMemBuilder::path
+/// [`offset`]: DevMemBuilder::offset
+/// [`len`]: DevMemBuilder::len
+/// [`address`]: DevMemBuilder::address
+#[cfg(feature = "device")]
+#[derive(Debug, Clone)]
+pub struct DevMemBuilder {
+    path: PathBuf,
+    offset: u64,
+    len: Option<usize>,
+    address: Option<usize>,
+    options: MapOptions,
+    policy: Option<AddressPolicy>,
+}
+
+#[cfg(feature = "device")]
+impl Default for DevMemBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "device")]
+impl DevMemBuilder {
+    /// Create a builder mapping one page of `/dev/mem` at offset `0`.
+    pub fn new() -> Self {
+        Self {
+            path: PathBuf::from("/dev/mem"),
+            offset: 0,
+            len: None,
+            address: None,
+            options: MapOptions::default(),
+            policy: None,
+        }
+    }
+
+    /// File to map (e.g. `/dev/uio0`, a sysfs `resourceN`, `/dev/fpga0`).
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Offset into the file at which the mapping starts. For `/dev/mem`
+    /// this is the physical address.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Length of the mapping in bytes.
+    pub fn len(mut self, len: usize) -> Self {
+        self.len = Some(len);
+        self
+    }
+
+    /// Physical address reported by [`DevMem::address`] and the generated
+    /// `*_address()` methods. Defaults to [`offset`](Self::offset).
+    pub fn address(mut self, address: usize) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// Opens the file with `O_SYNC`, which makes `/dev/mem` mappings
+    /// uncached on kernels that honour it.
+    pub fn sync(mut self, sync: bool) -> Self {
+        self.options.sync = sync;
+        self
+    }
+
+    /// Opens the file read-only and maps it `PROT_READ`, so only read
+    /// permission is needed. Writes through the `DevMem` fail with
+    /// [`Error::ReadOnly`].
+    ///
+    /// ```rust
+    /// use ddevmem::{DevMem, Error};
+    ///
+    /// let path = std::env::temp_dir().join("ddevmem-readonly-doctest.bin");
+    /// std::fs::write(&path, 0x1234_5678u32.to_ne_bytes()).unwrap();
+    ///
+    /// let devmem = unsafe { DevMem::builder().path(&path).read_only(true).build().unwrap() };
+    /// assert_eq!(devmem.read::<u32>(0), Some(0x1234_5678));
+    /// assert!(matches!(devmem.try_write(0, 0u32), Err(Error::ReadOnly { offset: 0 })));
+    /// assert!(format!("{devmem:?}").ends_with("ddevmem-readonly-doctest.bin, ro))"));
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.options.read_only = read_only;
+        self
+    }
+
+    /// Pre-faults the whole mapping (`MAP_POPULATE`).
+    pub fn populate(mut self, populate: bool) -> Self {
+        self.options.populate = populate;
+        self
+    }
+
+    /// Locks the mapping in memory (`mlock`, as with `MAP_LOCKED`).
+    pub fn locked(mut self, locked: bool) -> Self {
+        self.options.locked = locked;
+        self
+    }
+
+    /// Sets all [`MapOptions`] at once.
+    pub fn options(mut self, options: MapOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Checks the mapping against `policy` instead of the global
+    /// [`AddressPolicy`]. The range checked is [`address`](Self::address)
+    /// plus the mapping length; for `/dev/mem` the range actually mapped,
+    /// starting at [`offset`](Self::offset), must be permitted as well.
+    pub fn policy(mut self, policy: AddressPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Opens the file and creates the mapping.
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`DevMem::new`], applied to the memory behind the
+    /// configured path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AddressForbidden`] (before anything is mapped) if
+    /// the [`policy`](Self::policy) rejects the range, or
+    /// [`Error::CantOpenFile`] or [`Error::CantMmapFile`] carrying the
+    /// configured path (the latter also when [`locked`](Self::locked) pages
+    /// cannot be locked).
+    pub unsafe fn build(self) -> Result<DevMem<crate::backend::Mmap>, Error> {
+        let address = self.address.unwrap_or(self.offset as usize);
+        let dev_mem = self.path == Path::new("/dev/mem");
+        let global = AddressPolicy::global();
+        let policy = self.policy.as_ref().or(global.as_deref());
+        if policy.is_some() || dev_mem {
+            // The length the mapping will have, so the policy and the RAM
+            // warning see the range that actually gets mapped.
+            let len = match self.len {
+                Some(len) => len,
+                None => {
+                    let file_len = std::fs::metadata(&self.path)
+                        .map_err(|source| Error::CantOpenFile {
+                            path: self.path.clone(),
+                            source,
+                        })?
+                        .len();
+                    crate::backend::default_len(file_len, self.offset)
+                }
+            };
+            if let Some(policy) = policy {
+                policy.check(address, len)?;
+                // `address` only labels the mapping; on `/dev/mem` the
+                // offset is the physical range that gets mapped.
+                if dev_mem {
+                    policy.check(self.offset as usize, len)?;
+                }
+            }
+            if dev_mem {
+                crate::iomem::warn_if_ram(self.offset as usize, len);
+            }
+        }
+
+        let backend =
+            crate::backend::Mmap::open_with(&self.path, self.offset, self.len, self.options)?;
+        Ok(DevMem::from_backend(address, backend))
+    }
+}
+
+impl<B: Backend> DevMem<B> {
+    /// Wraps an existing backend. `address` is the physical address that
+    /// offset `0` of the backend corresponds to.
+    pub fn from_backend(address: usize, backend: B) -> Self {
+        let len = backend.len();
+        Self {
+            backend: Arc::new(backend),
+            claims: Registry::default(),
+            start: 0,
+            len,
+            address,
+            alignment: Alignment::Strict,
+            fault_tolerant: false,
+        }
+    }
+
+    /// Sets the policy for misaligned typed accesses (default
+    /// [`Alignment::Strict`]). Slices inherit the policy of their parent.
+    ///
+    /// Register map accessors are unaffected: their offsets are checked
+    /// against the bus width at compile time.
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Policy for misaligned typed accesses.
+    #[inline(always)]
+    pub fn alignment(&self) -> Alignment {
+        self.alignment
+    }
+
+    /// Enables or disables recovery from bus faults (default off). Slices
+    /// inherit the setting of their parent.
+    ///
+    /// When enabled, a checked access that raises SIGBUS or SIGSEGV — for
+    /// example on a powered-down IP block, or past the end of a truncated
+    /// file — fails with [`Error::BusFault`] (or `None`) instead of killing
+    /// the process. A process-wide signal handler is installed on first use
+    /// and forwards unrelated faults to the previous handler.
+    ///
+    /// Recovery requires backend support ([`Backend::catch_faults`]); only
+    /// [`Mmap`](crate::backend::Mmap) provides it. The unchecked accessors
+    /// used by [`register_map!`](crate::register_map) are not guarded.
+    ///
+    /// ```rust
+    /// use std::io::Write;
+    /// use ddevmem::{DevMem, Error};
+    ///
+    /// let page = page_size::get();
+    /// let path = std::env::temp_dir().join("ddevmem-busfault-doctest.bin");
+    /// std::fs::File::create(&path).unwrap().write_all(&vec![0xA5; page]).unwrap();
+    ///
+    /// // Map two pages of a one-page file: the second page faults.
+    /// let devmem = unsafe {
+    ///     DevMem::builder().path(&path).len(2 * page).build().unwrap()
+    /// }
+    /// .with_fault_tolerance(true);
+    ///
+    /// assert!(matches!(
+    ///     devmem.try_read::<u32>(page + 8),
+    ///     Err(Error::BusFault { offset }) if offset == page + 8
+    /// ));
+    /// assert!(matches!(devmem.try_write(page, 1u32), Err(Error::BusFault { .. })));
+    /// assert_eq!(devmem.read::<u32>(page), None);
+    /// assert_eq!(devmem.read::<u32>(0), Some(0xA5A5_A5A5));
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn with_fault_tolerance(mut self, enabled: bool) -> Self {
+        self.fault_tolerant = enabled;
+        self
+    }
+
+    /// Whether bus faults are recovered; see
+    /// [`with_fault_tolerance`](Self::with_fault_tolerance).
+    #[inline(always)]
+    pub fn fault_tolerant(&self) -> bool {
+        self.fault_tolerant
+    }
+
+    /// Returns a view of `len` bytes starting at `offset`, sharing the same
+    /// backend.
+    ///
+    /// The view reports `self.address() + offset` as its
+    /// [`address`](Self::address), bounds-checks against its own length, and
+    /// can be passed to a generated `new()` like any other `DevMem`.
+    ///
+    /// Returns `None` if `offset + len` exceeds the length of `self`.
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use ddevmem::{register_map, DevMem};
+    ///
+    /// register_map! {
+    ///     pub unsafe map Uart (u32) { 0x00 => rw data: u32 }
+    /// }
+    ///
+    /// let axi = unsafe { DevMem::new(0x4000_0000, Some(0x2000)).unwrap() };
+    /// let uart0 = axi.slice(0x0000, 0x100).unwrap();
+    /// let uart1 = axi.slice(0x1000, 0x100).unwrap();
+    /// assert_eq!(uart1.address(), 0x4000_1000);
+    ///
+    /// let mut uart1 = unsafe { Uart::new(Arc::new(uart1)).unwrap() };
+    /// uart1.set_data(0x55);
+    /// assert_eq!(axi.read::<u32>(0x1000), Some(0x55));
+    /// assert_eq!(uart0.read::<u32>(0x100), None);
+    /// ```
+    pub fn slice(&self, offset: usize, len: usize) -> Option<Self> {
+        if offset.checked_add(len)? > self.len {
+            return None;
         }
+        Some(Self {
+            backend: Arc::clone(&self.backend),
+            claims: self.claims.clone(),
+            start: self.start + offset,
+            len,
+            address: self.address + offset,
+            alignment: self.alignment,
+            fault_tolerant: self.fault_tolerant,
+        })
+    }
 
-        #[cfg(feature = "emulator")]
-        {
-            Ok(Self { mmap: UnsafeCell::new(vec![0; size]), address })
+    /// Claims `len` bytes starting at `offset` on behalf of `owner`.
+    ///
+    /// Claims are tracked per mapping: the `DevMem` returned by
+    /// [`new`](DevMem::new) / [`from_backend`](DevMem::from_backend) and all
+    /// of its [`slice`](DevMem::slice)s share one registry. The claim is
+    /// released when the returned [`Region`] is dropped. Unsafe constructors
+    /// such as a generated `new()` bypass the registry.
+    ///
+    /// ```rust
+    /// use ddevmem::{DevMem, Error};
+    ///
+    /// let devmem = unsafe { DevMem::new(0x4000_0000, Some(0x100)).unwrap() };
+    /// let uart = devmem.claim(0x00, 0x20, "uart").unwrap();
+    ///
+    /// let err = devmem.claim(0x10, 0x20, "spi").unwrap_err();
+    /// assert!(matches!(err, Error::RegionClaimed { holder: "uart", .. }));
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "cannot claim 0x40000010..0x40000030 for spi: overlaps uart at 0x40000000..0x40000020"
+    /// );
+    ///
+    /// drop(uart);
+    /// assert!(devmem.claim(0x10, 0x20, "spi").is_ok());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OutOfBounds`] if the range does not fit in `self`, or
+    /// [`Error::RegionClaimed`] if it overlaps a live claim.
+    pub fn claim(&self, offset: usize, len: usize, owner: &'static str) -> Result<Region, Error> {
+        match offset.checked_add(len) {
+            Some(end) if end <= self.len => {}
+            _ => {
+                return Err(Error::OutOfBounds {
+                    offset,
+                    size: len,
+                    len: self.len,
+                })
+            }
         }
+        self.claims
+            .claim(self.start + offset, len, self.address + offset, owner)
+            .map_err(|held| Error::RegionClaimed {
+                owner,
+                address: self.address + offset,
+                len,
+                holder: held.owner,
+                holder_address: held.address,
+                holder_len: held.len,
+            })
+    }
+
+    /// The backend performing the accesses.
+    ///
+    /// Offsets passed to the backend directly are relative to the whole
+    /// backend, not to this view.
+    #[inline(always)]
+    pub fn backend(&self) -> &B {
+        &self.backend
     }
 
-    /// Physical base address passed to [`DevMem::new`].
+    /// Physical base address of the region.
     #[inline(always)]
     pub fn address(&self) -> usize {
         self.address
@@ -143,57 +783,39 @@ impl DevMem {
     /// Length of the mapped region in bytes.
     #[inline(always)]
     pub fn len(&self) -> usize {
-        // SAFETY: no aliasing &mut to the inner buffer exists at this call site.
-        unsafe { (&*self.mmap.get()).len() }
+        self.len
     }
 
     /// Returns `true` when the mapped region has zero length.
     #[inline(always)]
     pub fn is_empty(&self) -> bool {
-        // SAFETY: same as len().
-        unsafe { (&*self.mmap.get()).is_empty() }
-    }
-
-    /// Raw pointer to the first byte of the mapped region.
-    ///
-    /// The returned pointer remains valid for the lifetime of `self`.
-    /// Use [`std::ptr::read_volatile`] / [`std::ptr::write_volatile`] to
-    /// access MMIO registers through this pointer. The caller must ensure no
-    /// conflicting accesses alias the same memory concurrently.
-    #[inline(always)]
-    pub fn as_ptr(&self) -> *mut u8 {
-        // SAFETY: UnsafeCell grants permission to derive a *mut from &self.
-        // Callers are responsible for synchronization.
-        unsafe { (*self.mmap.get()).as_ptr() as *mut u8 }
+        self.len == 0
     }
 
     /// Performs a volatile read of type `T` at `offset` bytes from the base.
     ///
     /// `T` must implement [`AnyBitPattern`] so that any bit pattern is a valid
-    /// value.
+    /// value. `T` is transferred as a run of bus accesses of width
+    /// `align_of::<T>()` (at most 8 bytes), so integers are read in a single
+    /// access.
     ///
-    /// Returns `None` if `offset + size_of::<T>()` exceeds the mapped length.
+    /// Returns `None` if the access is out of bounds or rejected as misaligned;
+    /// see [`try_read`](Self::try_read) for the reason.
     #[inline(always)]
     pub fn read<T: AnyBitPattern>(&self, offset: usize) -> Option<T> {
-        if offset + std::mem::size_of::<T>() > self.len() {
-            return None;
-        }
-        Some(unsafe { std::ptr::read_volatile(self.as_ptr().add(offset) as *const T) })
+        self.try_read(offset).ok()
     }
 
     /// Performs a volatile write of `value` at `offset` bytes from the base.
     ///
     /// `T` must implement [`NoUninit`] to guarantee no padding bytes are
-    /// written.
+    /// written. Access widths follow the same rule as [`read`](Self::read).
     ///
-    /// Returns `None` if `offset + size_of::<T>()` exceeds the mapped length.
+    /// Returns `None` if the access is out of bounds or rejected as misaligned;
+    /// see [`try_write`](Self::try_write) for the reason.
     #[inline(always)]
     pub fn write<T: NoUninit>(&self, offset: usize, value: T) -> Option<()> {
-        if offset + std::mem::size_of::<T>() > self.len() {
-            return None;
-        }
-        unsafe { std::ptr::write_volatile(self.as_ptr().add(offset) as *mut T, value) };
-        Some(())
+        self.try_write(offset, value).ok()
     }
 
     /// Volatile read-modify-write of type `T` at `offset`.
@@ -201,74 +823,739 @@ impl DevMem {
     /// Reads the current value, passes it to `f`, and writes the result back.
     /// The entire operation is **not** atomic.
     ///
-    /// Returns `None` if `offset + size_of::<T>()` exceeds the mapped length.
+    /// Returns `None` if the access is out of bounds or rejected as misaligned;
+    /// see [`try_modify`](Self::try_modify) for the reason.
     #[inline(always)]
     pub fn modify<T: AnyBitPattern + NoUninit>(
         &self,
         offset: usize,
         f: impl FnOnce(T) -> T,
     ) -> Option<()> {
-        if offset + std::mem::size_of::<T>() > self.len() {
-            return None;
-        }
-        unsafe {
-            let ptr = self.as_ptr().add(offset);
-            let val = std::ptr::read_volatile(ptr as *const T);
-            std::ptr::write_volatile(ptr as *mut T, f(val));
-        }
-        Some(())
+        self.try_modify(offset, f).ok()
     }
 
     /// Volatile read of `buf.len()` consecutive elements of type `T` starting
     /// at `offset`.
     ///
-    /// Each element is read with a separate [`std::ptr::read_volatile`].
+    /// Each element is read separately, as by [`read`](Self::read).
     ///
-    /// Returns `None` if `offset + size_of::<T>() * buf.len()` exceeds the
-    /// mapped length.
+    /// Returns `None` if the range is out of bounds or rejected as misaligned;
+    /// see [`try_read_slice`](Self::try_read_slice) for the reason.
     #[inline(always)]
     pub fn read_slice<T: AnyBitPattern>(&self, offset: usize, buf: &mut [T]) -> Option<()> {
-        if offset + std::mem::size_of_val(buf) > self.len() {
-            return None;
-        }
-        for (i, slot) in buf.iter_mut().enumerate() {
-            unsafe {
-                *slot = std::ptr::read_volatile(self.as_ptr().add(offset).cast::<T>().add(i));
-            }
-        }
-        Some(())
+        self.try_read_slice(offset, buf).ok()
     }
 
     /// Volatile write of `buf.len()` consecutive elements of type `T` starting
     /// at `offset`.
     ///
-    /// Each element is written with a separate [`std::ptr::write_volatile`].
-    /// `T: Copy` is required so that each element can be passed by value to
-    /// `write_volatile` without affecting the original slice.
+    /// Each element is written separately, as by [`write`](Self::write).
+    /// `T: Copy` is required so that each element can be passed by value
+    /// without affecting the original slice.
     ///
-    /// Returns `None` if `offset + size_of::<T>() * buf.len()` exceeds the
-    /// mapped length.
+    /// Returns `None` if the range is out of bounds or rejected as misaligned;
+    /// see [`try_write_slice`](Self::try_write_slice) for the reason.
     #[inline(always)]
     pub fn write_slice<T: NoUninit + Copy>(&self, offset: usize, buf: &[T]) -> Option<()> {
-        if offset + std::mem::size_of_val(buf) > self.len() {
-            return None;
+        self.try_write_slice(offset, buf).ok()
+    }
+
+    /// Like [`read`](Self::read), but reports why the access was rejected.
+    ///
+    /// ```rust
+    /// use ddevmem::{DevMem, Error};
+    ///
+    /// let devmem = unsafe { DevMem::new(0x4000_0000, Some(0x10)).unwrap() };
+    /// assert!(matches!(
+    ///     devmem.try_read::<u32>(0x10),
+    ///     Err(Error::OutOfBounds { offset: 0x10, size: 4, len: 0x10 })
+    /// ));
+    /// assert!(matches!(
+    ///     devmem.try_read::<u32>(0x02),
+    ///     Err(Error::Misaligned { offset: 0x02, align: 4 })
+    /// ));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OutOfBounds`] if `offset + size_of::<T>()` exceeds the
+    /// mapped length, or [`Error::Misaligned`] if the physical address
+    /// `address() + offset` is not a multiple of `align_of::<T>()` and the
+    /// [`Alignment`] policy is [`Strict`](Alignment::Strict). Fault-tolerant
+    /// instances also return [`Error::BusFault`].
+    #[inline(always)]
+    pub fn try_read<T: AnyBitPattern>(&self, offset: usize) -> Result<T, Error> {
+        let width = self.check::<T>(offset, std::mem::size_of::<T>())?;
+        self.guard(|| unsafe { self.read_value(offset, width) })
+    }
+
+    /// Like [`write`](Self::write), but reports why the access was rejected.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`try_read`](Self::try_read), plus [`Error::ReadOnly`] if the
+    /// mapping is read-only.
+    #[inline(always)]
+    pub fn try_write<T: NoUninit>(&self, offset: usize, value: T) -> Result<(), Error> {
+        let width = self.check::<T>(offset, std::mem::size_of::<T>())?;
+        self.check_writable(offset)?;
+        self.guard(|| unsafe { self.write_value(offset, value, width) })
+    }
+
+    /// Like [`modify`](Self::modify), but reports why the access was
+    /// rejected.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`try_write`](Self::try_write).
+    #[inline(always)]
+    pub fn try_modify<T: AnyBitPattern + NoUninit>(
+        &self,
+        offset: usize,
+        f: impl FnOnce(T) -> T,
+    ) -> Result<(), Error> {
+        let width = self.check::<T>(offset, std::mem::size_of::<T>())?;
+        self.check_writable(offset)?;
+        let val = self.guard(|| unsafe { self.read_value::<T>(offset, width) })?;
+        let val = f(val);
+        self.guard(|| unsafe { self.write_value(offset, val, width) })
+    }
+
+    /// Like [`read_slice`](Self::read_slice), but reports why the access was
+    /// rejected.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`try_read`](Self::try_read), with
+    /// `size_of::<T>() * buf.len()` as the access size.
+    #[inline(always)]
+    pub fn try_read_slice<T: AnyBitPattern>(
+        &self,
+        offset: usize,
+        buf: &mut [T],
+    ) -> Result<(), Error> {
+        let width = self.check::<T>(offset, std::mem::size_of_val(buf))?;
+        self.guard(|| {
+            for (i, slot) in buf.iter_mut().enumerate() {
+                *slot = unsafe { self.read_value(offset + i * std::mem::size_of::<T>(), width) };
+            }
+        })
+    }
+
+    /// Like [`write_slice`](Self::write_slice), but reports why the access
+    /// was rejected.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`try_read_slice`](Self::try_read_slice), plus
+    /// [`Error::ReadOnly`] if the mapping is read-only.
+    #[inline(always)]
+    pub fn try_write_slice<T: NoUninit + Copy>(
+        &self,
+        offset: usize,
+        buf: &[T],
+    ) -> Result<(), Error> {
+        let width = self.check::<T>(offset, std::mem::size_of_val(buf))?;
+        self.check_writable(offset)?;
+        self.guard(|| {
+            for (i, val) in buf.iter().enumerate() {
+                unsafe { self.write_value(offset + i * std::mem::size_of::<T>(), *val, width) };
+            }
+        })
+    }
+
+    /// Volatile read of a `T` at any `offset`.
+    ///
+    /// Uses the same bus accesses as [`read`](Self::read) when `offset` is
+    /// suitably aligned, and falls back to single-byte accesses otherwise.
+    ///
+    /// ```rust
+    /// use ddevmem::DevMem;
+    ///
+    /// let devmem = unsafe { DevMem::new(0x4000_0000, Some(0x28)).unwrap() };
+    /// devmem.write_slice(0x00, &[0x00u8, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99]);
+    ///
+    /// assert_eq!(devmem.read_unaligned::<u8>(0x01), Some(0x11));
+    /// assert_eq!(devmem.read_unaligned::<u16>(0x01), Some(0x2211));
+    /// assert_eq!(devmem.read_unaligned::<u32>(0x01), Some(0x4433_2211));
+    /// assert_eq!(devmem.read_unaligned::<u64>(0x01), Some(0x8877_6655_4433_2211));
+    /// assert_eq!(devmem.read::<u32>(0x01), None);
+    ///
+    /// devmem.write_unaligned(0x13, 0xAABBu16).unwrap();
+    /// devmem.write_unaligned(0x15, 0xCCDD_EEFFu32).unwrap();
+    /// devmem.write_unaligned(0x19, 0x0102_0304_0506_0708u64).unwrap();
+    /// assert_eq!(devmem.read_unaligned::<u16>(0x13), Some(0xAABB));
+    /// assert_eq!(devmem.read_unaligned::<u32>(0x15), Some(0xCCDD_EEFF));
+    /// assert_eq!(devmem.read_unaligned::<u64>(0x19), Some(0x0102_0304_0506_0708));
+    /// assert_eq!(devmem.read_unaligned::<u64>(0x21), None);
+    /// ```
+    ///
+    /// Returns `None` if the access is rejected; see
+    /// [`try_read_unaligned`](Self::try_read_unaligned) for the reason.
+    #[inline(always)]
+    pub fn read_unaligned<T: AnyBitPattern>(&self, offset: usize) -> Option<T> {
+        self.try_read_unaligned(offset).ok()
+    }
+
+    /// Volatile write of `value` at any `offset`; the counterpart of
+    /// [`read_unaligned`](Self::read_unaligned).
+    ///
+    /// Returns `None` if the access is rejected; see
+    /// [`try_write_unaligned`](Self::try_write_unaligned) for the reason.
+    #[inline(always)]
+    pub fn write_unaligned<T: NoUninit>(&self, offset: usize, value: T) -> Option<()> {
+        self.try_write_unaligned(offset, value).ok()
+    }
+
+    /// Like [`read_unaligned`](Self::read_unaligned), but reports why the
+    /// access was rejected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::OutOfBounds`] if `offset + size_of::<T>()` exceeds the
+    /// mapped length. Fault-tolerant instances also return
+    /// [`Error::BusFault`].
+    #[inline(always)]
+    pub fn try_read_unaligned<T: AnyBitPattern>(&self, offset: usize) -> Result<T, Error> {
+        let width = self.check_bounds::<T>(offset, std::mem::size_of::<T>())?;
+        self.guard(|| unsafe { self.read_value(offset, width) })
+    }
+
+    /// Like [`write_unaligned`](Self::write_unaligned), but reports why the
+    /// access was rejected.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`try_read_unaligned`](Self::try_read_unaligned), plus
+    /// [`Error::ReadOnly`] if the mapping is read-only.
+    #[inline(always)]
+    pub fn try_write_unaligned<T: NoUninit>(&self, offset: usize, value: T) -> Result<(), Error> {
+        let width = self.check_bounds::<T>(offset, std::mem::size_of::<T>())?;
+        self.check_writable(offset)?;
+        self.guard(|| unsafe { self.write_value(offset, value, width) })
+    }
+
+    /// Rejects writes to a read-only backend.
+    #[inline(always)]
+    fn check_writable(&self, offset: usize) -> Result<(), Error> {
+        if self.backend.is_read_only() {
+            return Err(Error::ReadOnly { offset });
+        }
+        Ok(())
+    }
+
+    /// Runs the accesses in `f`, turning a bus fault into
+    /// [`Error::BusFault`] when fault tolerance is enabled.
+    #[inline(always)]
+    fn guard<R>(&self, f: impl FnOnce() -> R) -> Result<R, Error> {
+        if !self.fault_tolerant {
+            return Ok(f());
         }
-        for (i, val) in buf.iter().enumerate() {
-            unsafe {
-                std::ptr::write_volatile(self.as_ptr().add(offset).cast::<T>().add(i), *val);
+        self.backend.catch_faults(f).map_err(|at| Error::BusFault {
+            offset: at.saturating_sub(self.start),
+        })
+    }
+
+    /// Validates an access of `size` bytes of `T`s at `offset` against the
+    /// length and the [`Alignment`] policy, returning the bus width to use.
+    #[inline(always)]
+    fn check<T>(&self, offset: usize, size: usize) -> Result<usize, Error> {
+        let width = self.check_bounds::<T>(offset, size)?;
+        if width == 1 && std::mem::align_of::<T>() > 1 && self.alignment == Alignment::Strict {
+            return Err(Error::Misaligned {
+                offset,
+                align: std::mem::align_of::<T>(),
+            });
+        }
+        Ok(width)
+    }
+
+    /// Validates the range only. Returns the natural bus width for `T`, or
+    /// `1` if `offset` is misaligned.
+    #[inline(always)]
+    fn check_bounds<T>(&self, offset: usize, size: usize) -> Result<usize, Error> {
+        match offset.checked_add(size) {
+            Some(end) if end <= self.len => {}
+            _ => {
+                return Err(Error::OutOfBounds {
+                    offset,
+                    size,
+                    len: self.len,
+                })
             }
         }
-        Some(())
+        if self.is_aligned(offset, std::mem::align_of::<T>()) {
+            Ok(std::mem::align_of::<T>().min(8))
+        } else {
+            Ok(1)
+        }
+    }
+
+    /// Whether `offset` is `align`-aligned both as a physical address and
+    /// within the backend, so that the host pointer is aligned for every
+    /// built-in backend.
+    #[inline(always)]
+    fn is_aligned(&self, offset: usize, align: usize) -> bool {
+        ((self.address + offset) | (self.start + offset)).is_multiple_of(align)
+    }
+
+    /// Single bus read of a `W` at `offset`, without a bounds check.
+    ///
+    /// Used by [`register_map!`](crate::register_map) accessors, whose
+    /// offsets are validated once in the generated `new()`.
+    ///
+    /// # Safety
+    ///
+    /// `offset + size_of::<W>()` must not exceed [`len`](Self::len).
+    #[inline(always)]
+    pub unsafe fn read_unchecked<W: Word>(&self, offset: usize) -> W {
+        debug_assert!(
+            self.is_aligned(offset, std::mem::size_of::<W>()),
+            "misaligned {offset:#X}"
+        );
+        self.backend.read(self.start + offset)
+    }
+
+    /// Single bus write of a `W` at `offset`, without a bounds check.
+    ///
+    /// # Safety
+    ///
+    /// `offset + size_of::<W>()` must not exceed [`len`](Self::len), and the
+    /// backend must not be read-only.
+    #[inline(always)]
+    pub unsafe fn write_unchecked<W: Word>(&self, offset: usize, value: W) {
+        debug_assert!(!self.backend.is_read_only(), "write to read-only mapping");
+        debug_assert!(
+            self.is_aligned(offset, std::mem::size_of::<W>()),
+            "misaligned {offset:#X}"
+        );
+        self.backend.write(self.start + offset, value)
+    }
+
+    /// Seeds the power-on value of a `W` at `offset` through
+    /// [`Backend::preset`], without a bounds check. A no-op on hardware.
+    ///
+    /// Used by generated `new()` for registers with declared reset values.
+    ///
+    /// # Safety
+    ///
+    /// `offset + size_of::<W>()` must not exceed [`len`](Self::len).
+    #[inline(always)]
+    pub unsafe fn preset_unchecked<W: Word>(&self, offset: usize, value: W) {
+        self.backend.preset(self.start + offset, value)
+    }
+
+    /// Read-modify-write of a `W` at `offset`, without a bounds check.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`write_unchecked`](Self::write_unchecked).
+    #[inline(always)]
+    pub unsafe fn modify_unchecked<W: Word>(&self, offset: usize, f: impl FnOnce(W) -> W) {
+        debug_assert!(!self.backend.is_read_only(), "write to read-only mapping");
+        debug_assert!(
+            self.is_aligned(offset, std::mem::size_of::<W>()),
+            "misaligned {offset:#X}"
+        );
+        self.backend.modify(self.start + offset, f)
+    }
+
+    /// Reads a `T` as a run of `width`-byte bus accesses.
+    ///
+    /// # Safety
+    ///
+    /// `offset + size_of::<T>()` must not exceed the mapped length, and
+    /// `offset` must be `width`-aligned.
+    #[inline(always)]
+    unsafe fn read_value<T: AnyBitPattern>(&self, offset: usize, width: usize) -> T {
+        let mut out = MaybeUninit::<T>::uninit();
+        let dst = out.as_mut_ptr() as *mut u8;
+        let size = std::mem::size_of::<T>();
+        match width {
+            1 => self.read_chunks::<u8>(offset, dst, size),
+            2 => self.read_chunks::<u16>(offset, dst, size),
+            4 => self.read_chunks::<u32>(offset, dst, size),
+            _ => self.read_chunks::<u64>(offset, dst, size),
+        }
+        out.assume_init()
+    }
+
+    /// Writes a `T` as a run of `width`-byte bus accesses.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`read_value`](Self::read_value).
+    #[inline(always)]
+    unsafe fn write_value<T: NoUninit>(&self, offset: usize, value: T, width: usize) {
+        let src = bytemuck::bytes_of(&value);
+        match width {
+            1 => self.write_chunks::<u8>(offset, src),
+            2 => self.write_chunks::<u16>(offset, src),
+            4 => self.write_chunks::<u32>(offset, src),
+            _ => self.write_chunks::<u64>(offset, src),
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn read_chunks<W: Word>(&self, offset: usize, dst: *mut u8, size: usize) {
+        let step = std::mem::size_of::<W>();
+        for i in (0..size).step_by(step) {
+            let word: W = self.backend.read(self.start + offset + i);
+            std::ptr::write_unaligned(dst.add(i) as *mut W, word);
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn write_chunks<W: Word>(&self, offset: usize, src: &[u8]) {
+        let step = std::mem::size_of::<W>();
+        for i in (0..src.len()).step_by(step) {
+            let word = std::ptr::read_unaligned(src.as_ptr().add(i) as *const W);
+            self.backend.write(self.start + offset + i, word);
+        }
+    }
+}
+
+#[cfg(feature = "device")]
+impl DevMem<crate::backend::Mmap> {
+    /// Starts building a mapping of an arbitrary device node or file.
+    ///
+    /// See [`DevMemBuilder`].
+    pub fn builder() -> DevMemBuilder {
+        DevMemBuilder::new()
+    }
+
+    /// Maps `len` bytes of an already-open file starting at `phys_offset`,
+    /// which is also reported as [`address`](Self::address).
+    ///
+    /// This lets a privileged helper open `/dev/mem` and hand the descriptor
+    /// to an unprivileged process (e.g. over a Unix socket with
+    /// `SCM_RIGHTS`), or a process open it and then drop root. Read-only
+    /// descriptors yield read-only mappings; see
+    /// [`Mmap::from_file`](crate::backend::Mmap::from_file).
+    ///
+    /// ```rust
+    /// use std::fs::OpenOptions;
+    /// use ddevmem::DevMem;
+    ///
+    /// let path = std::env::temp_dir().join("ddevmem-from-file-doctest.bin");
+    /// std::fs::write(&path, [0u8; 0x100]).unwrap();
+    ///
+    /// // "Privileged" step: open the file, then hand it over.
+    /// let file = OpenOptions::new().read(true).write(true).open(&path).unwrap();
+    /// let devmem = unsafe { DevMem::from_fd(file.into(), 0x40, 0x10).unwrap() };
+    /// assert_eq!(devmem.address(), 0x40);
+    /// devmem.write(0x04, 0xC0DEu32).unwrap();
+    ///
+    /// let file = std::fs::File::open(&path).unwrap();
+    /// let ro = unsafe { DevMem::from_file(file, 0x40, 0x10).unwrap() };
+    /// assert_eq!(ro.read::<u32>(0x04), Some(0xC0DE));
+    /// assert_eq!(ro.write(0x04, 0u32), None);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`DevMem::new`], applied to the memory behind
+    /// `file`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AddressForbidden`] if a global
+    /// [`AddressPolicy`](crate::policy::AddressPolicy) rejects the range,
+    /// [`Error::CantOpenFile`] if the descriptor cannot be inspected, or
+    /// [`Error::CantMmapFile`] if the `mmap` call fails.
+    pub unsafe fn from_file(
+        file: std::fs::File,
+        phys_offset: u64,
+        len: usize,
+    ) -> Result<Self, Error> {
+        crate::policy::check_global(phys_offset as usize, len)?;
+        let backend = crate::backend::Mmap::from_file(file, phys_offset, Some(len))?;
+        Ok(DevMem::from_backend(phys_offset as usize, backend))
+    }
+
+    /// [`from_file`](Self::from_file) for a raw owned descriptor.
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`from_file`](Self::from_file).
+    ///
+    /// # Errors
+    ///
+    /// Same as [`from_file`](Self::from_file).
+    pub unsafe fn from_fd(
+        fd: std::os::fd::OwnedFd,
+        phys_offset: u64,
+        len: usize,
+    ) -> Result<Self, Error> {
+        Self::from_file(fd.into(), phys_offset, len)
+    }
+
+    /// Raw pointer to the first byte of the mapped region.
+    ///
+    /// The returned pointer remains valid for the lifetime of `self`.
+    /// Use [`std::ptr::read_volatile`] / [`std::ptr::write_volatile`] to
+    /// access MMIO registers through this pointer. The caller must ensure no
+    /// conflicting accesses alias the same memory concurrently.
+    #[inline(always)]
+    pub fn as_ptr(&self) -> *mut u8 {
+        // SAFETY: `start` lies within the backend's region.
+        unsafe { self.backend.as_ptr().add(self.start) }
+    }
+}
+
+#[cfg(feature = "emulator")]
+impl DevMem<crate::backend::Emulator> {
+    /// Raw pointer to the first byte of the emulated region.
+    ///
+    /// The returned pointer remains valid for the lifetime of `self`. The
+    /// caller must ensure no conflicting accesses alias the same memory
+    /// concurrently.
+    #[inline(always)]
+    pub fn as_ptr(&self) -> *mut u8 {
+        // SAFETY: `start` lies within the backend's region.
+        unsafe { self.backend.as_ptr().add(self.start) }
     }
 }
 
-impl fmt::Debug for DevMem {
+impl<B: Backend + fmt::Debug> fmt::Debug for DevMem<B> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "DevMem({:#X}..{:#X})",
+            "DevMem({:#X}..{:#X}, {:?})",
             self.address,
-            self.address + self.len()
+            self.address + self.len(),
+            self.backend
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "device")]
+    #[test]
+    fn builder_policy_checks_the_mapped_dev_mem_range() {
+        let policy = AddressPolicy::new().allow(0x4000_0000, 0x1000);
+        let built = unsafe {
+            DevMem::builder()
+                .offset(0x8000_0000)
+                .address(0x4000_0000)
+                .len(0x1000)
+                .policy(policy)
+                .build()
+        };
+        assert!(matches!(
+            built,
+            Err(Error::AddressForbidden {
+                address: 0x8000_0000,
+                len: 0x1000
+            })
+        ));
+    }
+
+    #[cfg(feature = "device")]
+    #[test]
+    fn builder_policy_checks_the_address_of_other_files() {
+        let path = std::env::temp_dir().join(format!("ddevmem-policy-{}", std::process::id()));
+        std::fs::write(&path, [0u8; 0x100]).unwrap();
+        let build = |address| unsafe {
+            DevMem::builder()
+                .path(&path)
+                .address(address)
+                .policy(AddressPolicy::new().allow(0x4000_0000, 0x100))
+                .build()
+        };
+        assert_eq!(build(0x4000_0000).unwrap().address(), 0x4000_0000);
+        assert!(matches!(
+            build(0x5000_0000),
+            Err(Error::AddressForbidden { .. })
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "emulator")]
+    fn emulated(len: usize) -> DevMem<crate::backend::Emulator> {
+        DevMem::from_backend(0x4000_0000, crate::backend::Emulator::new(len))
+    }
+
+    #[cfg(feature = "emulator")]
+    #[test]
+    fn typed_accesses_cover_every_width() {
+        let devmem = emulated(0x20);
+        devmem.try_write(0x00, 0xA5u8).unwrap();
+        devmem.try_write(0x02, 0xBEEFu16).unwrap();
+        devmem.try_write(0x04, 0xDEAD_BEEFu32).unwrap();
+        devmem.try_write(0x08, 0x0123_4567_89AB_CDEFu64).unwrap();
+        assert_eq!(devmem.try_read::<u8>(0x00).unwrap(), 0xA5);
+        assert_eq!(devmem.try_read::<u16>(0x02).unwrap(), 0xBEEF);
+        assert_eq!(devmem.try_read::<u32>(0x04).unwrap(), 0xDEAD_BEEF);
+        assert_eq!(devmem.try_read::<u64>(0x08).unwrap(), 0x0123_4567_89AB_CDEF);
+
+        let mut bytes = [0u8; 4];
+        devmem.try_read_slice(0x04, &mut bytes).unwrap();
+        assert_eq!(bytes, 0xDEAD_BEEFu32.to_ne_bytes());
+        devmem.try_modify(0x04, |v: u32| v + 1).unwrap();
+        assert_eq!(devmem.read::<u32>(0x04), Some(0xDEAD_BEF0));
+    }
+
+    #[cfg(feature = "emulator")]
+    #[test]
+    fn misaligned_and_out_of_bounds_accesses_are_rejected() {
+        let devmem = emulated(0x20);
+        assert!(matches!(
+            devmem.try_read::<u32>(0x02),
+            Err(Error::Misaligned {
+                offset: 0x02,
+                align: 4
+            })
+        ));
+        assert!(matches!(
+            devmem.try_write(0x01, 0u16),
+            Err(Error::Misaligned {
+                offset: 0x01,
+                align: 2
+            })
+        ));
+        assert!(matches!(
+            devmem.try_read_slice(0x04, &mut [0u64; 1]),
+            Err(Error::Misaligned {
+                offset: 0x04,
+                align: 8
+            })
+        ));
+        // Bounds are checked before alignment.
+        assert!(matches!(
+            devmem.try_read::<u64>(0x1C),
+            Err(Error::OutOfBounds {
+                offset: 0x1C,
+                size: 8,
+                len: 0x20
+            })
+        ));
+        assert!(matches!(
+            devmem.try_read::<u64>(0x20),
+            Err(Error::OutOfBounds {
+                offset: 0x20,
+                size: 8,
+                len: 0x20
+            })
+        ));
+        assert!(matches!(
+            devmem.try_write(usize::MAX, 0u8),
+            Err(Error::OutOfBounds {
+                offset: usize::MAX,
+                size: 1,
+                len: 0x20
+            })
+        ));
+        assert_eq!(devmem.read::<u8>(0x1F), Some(0));
+        assert_eq!(devmem.read::<u16>(0x1F), None);
+    }
+
+    #[cfg(feature = "emulator")]
+    #[test]
+    fn alignment_follows_the_physical_address() {
+        let devmem = emulated(0x20);
+        let view = devmem.slice(0x04, 0x10).unwrap();
+        assert_eq!(view.address(), 0x4000_0004);
+        assert!(view.try_write(0x00, 0x1122_3344u32).is_ok());
+        assert!(matches!(
+            view.try_read::<u64>(0x00),
+            Err(Error::Misaligned { align: 8, .. })
+        ));
+        assert_eq!(
+            view.try_read::<u64>(0x04).unwrap(),
+            devmem.read::<u64>(0x08).unwrap()
+        );
+
+        let shifted = DevMem::from_backend(0x4000_0002, crate::backend::Emulator::new(0x10));
+        assert!(matches!(
+            shifted.try_read::<u32>(0x00),
+            Err(Error::Misaligned { .. })
+        ));
+        assert!(matches!(
+            shifted.try_read::<u32>(0x02),
+            Err(Error::Misaligned { .. })
+        ));
+    }
+
+    #[cfg(feature = "emulator")]
+    #[test]
+    fn split_and_unaligned_accesses_go_byte_by_byte() {
+        let devmem = emulated(0x10).with_alignment(Alignment::Split);
+        devmem.try_write(0x01, 0x1122_3344u32).unwrap();
+        assert_eq!(devmem.try_read::<u32>(0x01).unwrap(), 0x1122_3344);
+        assert_eq!(
+            devmem.read::<u8>(0x01),
+            Some(0x1122_3344u32.to_ne_bytes()[0])
+        );
+
+        let strict = emulated(0x10);
+        strict
+            .try_write_unaligned(0x03, 0x0102_0304_0506_0708u64)
+            .unwrap();
+        assert_eq!(
+            strict.try_read_unaligned::<u64>(0x03).unwrap(),
+            0x0102_0304_0506_0708
+        );
+        assert!(strict.read::<u64>(0x03).is_none());
+        assert!(matches!(
+            strict.try_read_unaligned::<u64>(0x09),
+            Err(Error::OutOfBounds {
+                offset: 0x09,
+                size: 8,
+                len: 0x10
+            })
+        ));
+        assert!(matches!(
+            strict.try_write_unaligned(0x0F, 0u16),
+            Err(Error::OutOfBounds {
+                offset: 0x0F,
+                size: 2,
+                len: 0x10
+            })
+        ));
+    }
+
+    #[cfg(feature = "device")]
+    #[test]
+    fn from_file_follows_the_descriptor_mode() {
+        use std::{fs::OpenOptions, os::unix::fs::OpenOptionsExt};
+
+        let path = std::env::temp_dir().join(format!("ddevmem-from-fd-{}", std::process::id()));
+        std::fs::write(&path, [0u8; 0x100]).unwrap();
+
+        let ro = std::fs::File::open(&path).unwrap();
+        let ro = unsafe { DevMem::from_file(ro, 0x40, 0x10).unwrap() };
+        assert!(matches!(
+            ro.try_write(0x04, 1u32),
+            Err(Error::ReadOnly { offset: 0x04 })
+        ));
+        assert!(format!("{ro:?}").contains("/proc/self/fd/"));
+
+        let sync = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_SYNC)
+            .open(&path)
+            .unwrap();
+        let sync = unsafe { DevMem::from_fd(sync.into(), 0, 0x10).unwrap() };
+        assert!(format!("{sync:?}").ends_with(", rw, sync))"), "{sync:?}");
+        sync.try_write(0x04, 1u32).unwrap();
+
+        let wo = OpenOptions::new().write(true).open(&path).unwrap();
+        assert!(matches!(
+            unsafe { DevMem::from_fd(wo.into(), 0, 0x10) },
+            Err(Error::CantMmapFile { .. })
+        ));
+
+        let dir = std::fs::File::open(std::env::temp_dir()).unwrap();
+        assert!(matches!(
+            unsafe { DevMem::from_file(dir, 0, 0x10) },
+            Err(Error::CantMmapFile { .. })
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
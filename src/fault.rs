@@ -0,0 +1,138 @@
+//! SIGBUS / SIGSEGV recovery for fault-tolerant [`Mmap`](crate::backend::Mmap)
+//! accesses.
+//!
+//! A process-wide handler is installed on first use. While a guarded access
+//! runs, a fault inside the guarded range is resolved by mapping an anonymous
+//! page over the faulting one, so the instruction completes (reads see
+//! zeros, writes are discarded); the caller is told afterwards and restores
+//! the real mapping. Faults anywhere else are forwarded to the previously
+//! installed handler, or to the default action.
+
+use std::{
+    cell::Cell,
+    ops::Range,
+    ptr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+};
+
+use libc::{c_int, c_void, siginfo_t};
+
+thread_local! {
+    /// Host address range whose faults are recovered on this thread;
+    /// `(0, 0)` outside a guarded access.
+    static GUARD: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+    /// First faulting address recovered during the current guard, `0` if
+    /// none.
+    static FAULT: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Handlers that were installed before ours.
+struct Previous {
+    bus: libc::sigaction,
+    segv: libc::sigaction,
+}
+
+static PREVIOUS: OnceLock<Previous> = OnceLock::new();
+
+/// Page size, cached so the handler does not have to query it.
+static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// Runs `f`, recovering from faults on host addresses in `range`.
+///
+/// Returns the result of `f` and the first faulting address, if any. Pages
+/// that faulted are left replaced by anonymous memory and must be remapped
+/// by the caller.
+pub(crate) fn catch<R>(range: Range<usize>, f: impl FnOnce() -> R) -> (R, Option<usize>) {
+    install();
+    let outer_guard = GUARD.replace((range.start, range.end));
+    let outer_fault = FAULT.replace(0);
+    let result = f();
+    GUARD.set(outer_guard);
+    let fault = FAULT.replace(outer_fault);
+    (result, (fault != 0).then_some(fault))
+}
+
+fn install() {
+    PREVIOUS.get_or_init(|| {
+        PAGE_SIZE.store(page_size::get(), Ordering::Relaxed);
+        // SAFETY: `action` is fully initialized before use and `handler`
+        // has the signature required by `SA_SIGINFO`.
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handler as *const () as libc::sighandler_t;
+            action.sa_flags = libc::SA_SIGINFO | libc::SA_NODEFER | libc::SA_ONSTACK;
+            libc::sigemptyset(&mut action.sa_mask);
+
+            let mut bus: libc::sigaction = std::mem::zeroed();
+            let mut segv: libc::sigaction = std::mem::zeroed();
+            libc::sigaction(libc::SIGBUS, &action, &mut bus);
+            libc::sigaction(libc::SIGSEGV, &action, &mut segv);
+            Previous { bus, segv }
+        }
+    });
+}
+
+extern "C" fn handler(sig: c_int, info: *mut siginfo_t, ctx: *mut c_void) {
+    // SAFETY: the kernel passes a valid `siginfo_t` for SA_SIGINFO handlers.
+    let addr = unsafe { (*info).si_addr() } as usize;
+    let (start, end) = GUARD.try_with(Cell::get).unwrap_or((0, 0));
+
+    if (start..end).contains(&addr) && replace_page(addr) {
+        let _ = FAULT.try_with(|fault| {
+            if fault.get() == 0 {
+                fault.set(addr);
+            }
+        });
+        return;
+    }
+
+    // SAFETY: forwarding the arguments we were called with.
+    unsafe { forward(sig, info, ctx) }
+}
+
+/// Maps an anonymous page over the one containing `addr`.
+fn replace_page(addr: usize) -> bool {
+    let page = PAGE_SIZE.load(Ordering::Relaxed);
+    // SAFETY: the page lies inside a live guarded mapping owned by the
+    // faulting access; replacing it does not affect any Rust allocation.
+    let mapped = unsafe {
+        libc::mmap(
+            (addr & !(page - 1)) as *mut c_void,
+            page,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_FIXED,
+            -1,
+            0,
+        )
+    };
+    mapped != libc::MAP_FAILED
+}
+
+/// Hands the signal to the previous handler, or restores the default action
+/// so that the re-executed instruction terminates the process as usual.
+unsafe fn forward(sig: c_int, info: *mut siginfo_t, ctx: *mut c_void) {
+    let previous = PREVIOUS
+        .get()
+        .map(|p| if sig == libc::SIGBUS { p.bus } else { p.segv });
+
+    match previous {
+        Some(prev) if prev.sa_sigaction != libc::SIG_DFL && prev.sa_sigaction != libc::SIG_IGN => {
+            if prev.sa_flags & libc::SA_SIGINFO != 0 {
+                let f: extern "C" fn(c_int, *mut siginfo_t, *mut c_void) =
+                    std::mem::transmute(prev.sa_sigaction);
+                f(sig, info, ctx);
+            } else {
+                let f: extern "C" fn(c_int) = std::mem::transmute(prev.sa_sigaction);
+                f(sig);
+            }
+        }
+        _ => {
+            let mut default: libc::sigaction = std::mem::zeroed();
+            default.sa_sigaction = libc::SIG_DFL;
+            libc::sigaction(sig, &default, ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,358 @@
+//! `/proc/iomem` parser.
+//!
+//! The kernel lists every claimed physical range in `/proc/iomem`, nested
+//! by indentation:
+//!
+//! ```text
+//! 00000000-3fffffff : System RAM
+//!   00008000-00afffff : Kernel code
+//! 43c00000-43c0ffff : 43c00000.timer
+//!   43c00000-43c0ffff : uio
+//! ```
+//!
+//! [`parse`] turns such a file into a tree of [`Resource`]s that can answer
+//! which driver owns an address, find a device's range by name, or tell
+//! whether a range is RAM. [`DevMem::new`](crate::DevMem::new) uses it to
+//! warn on stderr when asked to map `System RAM`; [`set_ram_warnings`]
+//! turns that off.
+//!
+//! ```rust
+//! use ddevmem::iomem;
+//!
+//! let path = std::env::temp_dir().join("ddevmem-iomem-doctest");
+//! std::fs::write(
+//!     &path,
+//!     "00000000-3fffffff : System RAM\n  00008000-00afffff : Kernel code\n\
+//!      43c00000-43c0ffff : 43c00000.timer\n  43c00000-43c0ffff : uio\n",
+//! )
+//! .unwrap();
+//!
+//! let map = iomem::parse(&path).unwrap();
+//! assert_eq!(map.resources().len(), 2);
+//!
+//! let owner = map.owner(0x43C0_0010).unwrap();
+//! assert_eq!(owner.name, "uio");
+//!
+//! let timer = map.find("timer").unwrap();
+//! assert_eq!((timer.start, timer.len()), (0x43C0_0000, 0x1_0000));
+//!
+//! assert_eq!(map.ram_overlapping(0x0100_0000, 0x1000).unwrap().name, "System RAM");
+//! assert!(map.ram_overlapping(0x43C0_0000, 0x1000).is_none());
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+//!
+//! Unprivileged readers see every address as zero;
+//! [`Iomem::is_redacted`] reports that case.
+
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::Error;
+
+/// Path of the kernel's resource map.
+pub const PROC_IOMEM: &str = "/proc/iomem";
+
+/// Name the kernel gives to memory it manages.
+pub const SYSTEM_RAM: &str = "System RAM";
+
+static RAM_WARNINGS: AtomicBool = AtomicBool::new(true);
+
+/// One entry of `/proc/iomem` and the entries nested under it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resource {
+    /// First physical address.
+    pub start: u64,
+    /// Last physical address (inclusive, as printed by the kernel).
+    pub end: u64,
+    /// Name, e.g. `System RAM`, `43c00000.timer` or a driver name.
+    pub name: String,
+    /// Nested resources.
+    pub children: Vec<Resource>,
+}
+
+impl Resource {
+    /// Length in bytes.
+    pub fn len(&self) -> u64 {
+        (self.end - self.start).saturating_add(1)
+    }
+
+    /// Always `false`: a resource spans at least one byte.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Whether `address` lies inside the resource.
+    pub fn contains(&self, address: u64) -> bool {
+        (self.start..=self.end).contains(&address)
+    }
+
+    /// Whether any of `len` bytes at `address` lie inside the resource.
+    pub fn overlaps(&self, address: u64, len: u64) -> bool {
+        len > 0 && address <= self.end && self.start <= address.saturating_add(len - 1)
+    }
+
+    /// Whether `name` matches this resource, either exactly or as the part
+    /// after the unit address of a device-tree name (`timer` matches
+    /// `43c00000.timer`).
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name == name
+            || self
+                .name
+                .split_once('.')
+                .is_some_and(|(_, node)| node == name)
+    }
+}
+
+/// Parsed `/proc/iomem`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Iomem {
+    resources: Vec<Resource>,
+}
+
+impl Iomem {
+    /// Top-level resources in file order.
+    pub fn resources(&self) -> &[Resource] {
+        &self.resources
+    }
+
+    /// All resources, depth first.
+    pub fn iter(&self) -> impl Iterator<Item = &Resource> {
+        let mut stack: Vec<&Resource> = self.resources.iter().rev().collect();
+        std::iter::from_fn(move || {
+            let next = stack.pop()?;
+            stack.extend(next.children.iter().rev());
+            Some(next)
+        })
+    }
+
+    /// Chain of resources containing `address`, outermost first.
+    pub fn owners(&self, address: u64) -> Vec<&Resource> {
+        let mut chain = Vec::new();
+        let mut level = &self.resources;
+        while let Some(r) = level.iter().find(|r| r.contains(address)) {
+            chain.push(r);
+            level = &r.children;
+        }
+        chain
+    }
+
+    /// Innermost resource containing `address` — usually the driver or
+    /// device that claimed it.
+    pub fn owner(&self, address: u64) -> Option<&Resource> {
+        self.owners(address).pop()
+    }
+
+    /// First resource (depth first) matching `name`; see
+    /// [`Resource::is_named`].
+    pub fn find(&self, name: &str) -> Option<&Resource> {
+        self.iter().find(|r| r.is_named(name))
+    }
+
+    /// First `System RAM` resource overlapping `len` bytes at `address`.
+    pub fn ram_overlapping(&self, address: u64, len: u64) -> Option<&Resource> {
+        self.iter()
+            .find(|r| r.name == SYSTEM_RAM && r.overlaps(address, len))
+    }
+
+    /// Whether the addresses were hidden from an unprivileged reader (all
+    /// ranges read as zero).
+    pub fn is_redacted(&self) -> bool {
+        !self.resources.is_empty() && self.iter().all(|r| r.start == 0 && r.end == 0)
+    }
+}
+
+impl FromStr for Iomem {
+    type Err = io::Error;
+
+    /// Parses iomem-formatted text; nesting is two spaces per level.
+    fn from_str(text: &str) -> io::Result<Self> {
+        let mut root = Iomem::default();
+        // Index of the last resource at each level, from the root down.
+        let mut depth_path: Vec<usize> = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid =
+                || io::Error::new(ErrorKind::InvalidData, format!("line {}: {line:?}", n + 1));
+            let indent = line.len() - line.trim_start().len();
+            let depth = indent / 2;
+            let (range, name) = line.trim_start().split_once(" : ").ok_or_else(invalid)?;
+            let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+            let start = u64::from_str_radix(start, 16).map_err(|_| invalid())?;
+            let end = u64::from_str_radix(end, 16).map_err(|_| invalid())?;
+            if end < start || depth > depth_path.len() {
+                return Err(invalid());
+            }
+
+            depth_path.truncate(depth);
+            let mut level = &mut root.resources;
+            for &i in &depth_path {
+                level = &mut level[i].children;
+            }
+            level.push(Resource {
+                start,
+                end,
+                name: name.to_owned(),
+                children: Vec::new(),
+            });
+            depth_path.push(level.len() - 1);
+        }
+        Ok(root)
+    }
+}
+
+/// Reads and parses an iomem-formatted file, normally [`PROC_IOMEM`].
+///
+/// # Errors
+///
+/// Returns [`Error::CantReadFile`] if the file cannot be read or a line is
+/// malformed; the error names the offending line.
+pub fn parse(path: impl AsRef<Path>) -> Result<Iomem, Error> {
+    let path = path.as_ref();
+    fs::read_to_string(path)
+        .and_then(|text| text.parse())
+        .map_err(|source| Error::CantReadFile {
+            path: path.to_owned(),
+            source,
+        })
+}
+
+/// Reads [`PROC_IOMEM`].
+///
+/// # Errors
+///
+/// Same as [`parse`].
+pub fn system() -> Result<Iomem, Error> {
+    parse(PROC_IOMEM)
+}
+
+/// Enables or disables the stderr warning printed when
+/// [`DevMem::new`](crate::DevMem::new) or a `/dev/mem` builder maps
+/// `System RAM`. On by default.
+///
+/// With warnings off, `/proc/iomem` is not read at all. To act on the
+/// overlap instead, call [`system`] and [`Iomem::ram_overlapping`] before
+/// mapping.
+pub fn set_ram_warnings(enabled: bool) {
+    RAM_WARNINGS.store(enabled, Ordering::Relaxed);
+}
+
+/// Prints a warning if `len` bytes at `address` overlap `System RAM`.
+/// Best effort: an unreadable or redacted `/proc/iomem` is ignored.
+///
+/// `/proc/iomem` is read once per process; RAM does not move at run time.
+#[cfg(feature = "device")]
+pub(crate) fn warn_if_ram(address: usize, len: usize) {
+    static SYSTEM: std::sync::OnceLock<Option<Iomem>> = std::sync::OnceLock::new();
+
+    if !RAM_WARNINGS.load(Ordering::Relaxed) {
+        return;
+    }
+    let Some(iomem) = SYSTEM.get_or_init(|| system().ok().filter(|m| !m.is_redacted())) else {
+        return;
+    };
+    if let Some(ram) = iomem.ram_overlapping(address as u64, len as u64) {
+        eprintln!(
+            "ddevmem: warning: mapping {address:#X}..{:#X} overlaps {SYSTEM_RAM} at {:#X}..{:#X}",
+            address.saturating_add(len),
+            ram.start,
+            ram.end.saturating_add(1)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NESTED: &str = "\
+00000000-3fffffff : System RAM
+  00008000-00afffff : Kernel code
+  00c00000-00cfffff : Kernel data
+40000000-4fffffff : soc
+  43c00000-43c0ffff : 43c00000.timer
+    43c00000-43c0ffff : uio
+  43c10000-43c1ffff : 43c10000.gpio
+";
+
+    #[test]
+    fn parse_builds_the_nesting_tree() {
+        let map: Iomem = NESTED.parse().unwrap();
+        assert_eq!(map.resources().len(), 2);
+        let ram = &map.resources()[0];
+        assert_eq!(ram.children.len(), 2);
+        assert_eq!(ram.children[1].name, "Kernel data");
+        let soc = &map.resources()[1];
+        assert_eq!(soc.children.len(), 2);
+        assert_eq!(soc.children[0].children[0].name, "uio");
+        assert!(soc.children[1].children.is_empty());
+
+        let names: Vec<&str> = map.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "System RAM",
+                "Kernel code",
+                "Kernel data",
+                "soc",
+                "43c00000.timer",
+                "uio",
+                "43c10000.gpio",
+            ]
+        );
+    }
+
+    #[test]
+    fn owners_follow_the_nesting() {
+        let map: Iomem = NESTED.parse().unwrap();
+        let chain: Vec<&str> = map
+            .owners(0x43C0_0004)
+            .into_iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(chain, ["soc", "43c00000.timer", "uio"]);
+        assert_eq!(map.owner(0x43C1_0000).unwrap().name, "43c10000.gpio");
+        assert_eq!(map.owner(0x4400_0000).unwrap().name, "soc");
+        assert!(map.owner(0x5000_0000).is_none());
+        assert_eq!(map.find("gpio").unwrap().start, 0x43C1_0000);
+    }
+
+    #[test]
+    fn ram_overlap_checks_both_ends() {
+        let map: Iomem = NESTED.parse().unwrap();
+        assert!(map.ram_overlapping(0x3FFF_F000, 0x2000).is_some());
+        assert!(map.ram_overlapping(0x4000_0000, 0x1000).is_none());
+        assert!(map.ram_overlapping(0x3FFF_FFFF, 0).is_none());
+    }
+
+    #[test]
+    fn parse_rejects_malformed_lines() {
+        for text in [
+            "00000000-0000ffff System RAM\n",
+            "0000zzzz-0000ffff : RAM\n",
+            "0000ffff-00000000 : backwards\n",
+            "00000000-0000ffff : a\n    00000000-000000ff : skips a level\n",
+        ] {
+            let err = text.parse::<Iomem>().unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{text:?}");
+        }
+        let err = "00000000-0000ffff : a\nbad\n".parse::<Iomem>().unwrap_err();
+        assert!(err.to_string().starts_with("line 2:"), "{err}");
+    }
+
+    #[test]
+    fn redacted_maps_are_detected() {
+        let map: Iomem = "00000000-00000000 : System RAM\n  00000000-00000000 : Kernel code\n"
+            .parse()
+            .unwrap();
+        assert!(map.is_redacted());
+        assert!(!Iomem::default().is_redacted());
+        assert!(!NESTED.parse::<Iomem>().unwrap().is_redacted());
+    }
+}
@@ -6,7 +6,16 @@
 //! This crate provides:
 //!
 //! - [`DevMem`] — memory-mapped access to a physical address range with
-//!   volatile read, write, and modify operations.
+//!   volatile read, write, and modify operations, generic over a pluggable
+//!   [`Backend`].
+//! - [`uio::Uio`] — Linux UIO devices: memory maps discovered from sysfs
+//!   plus interrupt wait / acknowledge.
+//! - [`iomem`] — `/proc/iomem` parser: which driver owns an address, where
+//!   a device lives, what is RAM.
+//! - [`devicetree::DeviceTree`] — base addresses from `/proc/device-tree`
+//!   by `compatible` string or label.
+//! - [`policy::AddressPolicy`] — allowlist / denylist of physical ranges,
+//!   checked before anything is mapped.
 //! - [`register_map!`] — declarative macro for defining named register maps
 //!   with optional bus-width enforcement, bitfield accessors, and typed
 //!   bitfields (`as bool` / `as u8` / `as enum`) (requires the
@@ -17,12 +26,18 @@
 //! | Feature          | Default | Description |
 //! |------------------|---------|-------------|
 //! | `device`         | yes     | Real `/dev/mem` backend via `memmap2`. |
-//! | `emulator`       | no      | In-memory `Vec<u8>` backend for testing without hardware. |
+//! | `emulator`       | no      | In-memory heap buffer backend for testing without hardware. |
+//! | `default-device` | no      | With both backends, make [`DevMem::new`] use `/dev/mem`. |
+//! | `default-emulator` | no    | With both backends, make [`DevMem::new`] use the emulator. |
 //! | `register-map`   | yes     | [`register_map!`] macro with bitfields and typed accessors. |
 //! | `web`            | no      | Web UI for viewing/editing registers via [`axum`]. |
+//! | `async`          | no      | `async` interrupt waits for [`uio::Uio`] on Tokio. |
+//! | `broker`         | no      | `ddevmem-broker` daemon and [`broker`] client for unprivileged access. |
 //!
-//! Enable exactly one of `device` or `emulator`. When both are enabled
-//! simultaneously the `emulator` backend takes precedence.
+//! Both backends can be enabled at once and used side by side through
+//! [`DevMem::from_backend`]. [`DevMem::new`] uses [`DefaultBackend`], which is
+//! whichever backend is enabled; with both, `default-device` or
+//! `default-emulator` has to pick one or the build fails.
 //!
 //! ## Quick start
 //!
@@ -60,23 +75,73 @@
 //! regs.modify_control(|v| v | 1);
 //! ```
 
+#[cfg(all(
+    feature = "device",
+    feature = "emulator",
+    not(any(feature = "default-device", feature = "default-emulator"))
+))]
+compile_error!(
+    "features `device` and `emulator` are both enabled; enable `default-device` or \
+     `default-emulator` to choose the backend `DevMem::new` uses"
+);
+
+#[cfg(all(feature = "default-device", feature = "default-emulator"))]
+compile_error!("features `default-device` and `default-emulator` are mutually exclusive");
+
 #[cfg(any(feature = "device", feature = "emulator"))]
 mod devmem;
 
+#[cfg(any(feature = "device", feature = "emulator"))]
+pub mod backend;
+
+#[cfg(any(feature = "device", feature = "emulator"))]
+mod region;
+
 #[cfg(any(feature = "device", feature = "emulator"))]
 #[doc(inline)]
-pub use devmem::{DevMem, Error};
+pub use devmem::{Alignment, DevMem, Error};
+
+#[cfg(any(feature = "device", feature = "emulator"))]
+#[doc(inline)]
+pub use region::Region;
+
+#[cfg(feature = "device")]
+#[doc(inline)]
+pub use devmem::DevMemBuilder;
+
+#[cfg(any(feature = "device", feature = "emulator"))]
+#[doc(inline)]
+pub use backend::{Backend, DefaultBackend};
+
+#[cfg(any(feature = "device", feature = "emulator"))]
+pub mod iomem;
+
+#[cfg(any(feature = "device", feature = "emulator"))]
+pub mod devicetree;
+
+#[cfg(feature = "device")]
+pub mod uio;
+
+#[cfg(feature = "device")]
+mod fault;
+
+#[cfg(feature = "device")]
+pub mod policy;
+
+#[cfg(feature = "broker")]
+pub mod broker;
 
 #[cfg(feature = "web")]
 pub mod web;
 
 /// Declares a named register map backed by a [`DevMem`] instance.
 ///
-/// Each entry specifies an offset, access kind (`rw` / `ro` / `wo`), a name,
-/// and a type. An optional bus-width type can be given in parentheses after
-/// the map name to enforce that every register access goes through the
-/// bus-native width (e.g. `u32` for AXI-Lite); register types narrower than
-/// the bus are truncated / zero-extended automatically.
+/// Each entry specifies an offset, access kind (`rw` / `ro` / `wo`, or one
+/// of the [side-effecting kinds](#access-kinds)), a name, and a type. An
+/// optional bus-width type can be given in parentheses after the map name
+/// to enforce that every register access goes through the bus-native width
+/// (e.g. `u32` for AXI-Lite); register types narrower than the bus are
+/// truncated / zero-extended automatically.
 ///
 /// When no bus width is specified the default is `usize` (the native pointer
 /// width). On a 32-bit target this equals `u32`; on 64-bit it equals `u64`.
@@ -97,9 +162,136 @@ pub mod web;
 /// - `field: bit as bool` — getter returns `bool`, setter accepts `bool`.
 /// - `field: lo..=hi as u8` — getter returns `u8`, setter accepts `u8`
 ///   (any integer type is supported).
+/// - `field: lo..=hi as i8` (any signed type) — the field is two's
+///   complement: the getter sign-extends from bit `hi`, and the setter
+///   panics if the value does not fit the field. `try_set_reg_field` and
+///   the value type's `try_with_field` return the value back as `Err`
+///   instead. A type narrower than the field is a compile error.
 /// - `field: lo..=hi as enum Name { Variant = value, ... }` — generates a
-///   `#[derive(Debug, Clone, Copy, PartialEq, Eq)]` enum with `from_raw()`
-///   and `to_raw()` methods. Unknown raw values map to the first variant.
+///   `#[derive(Debug, Clone, Copy, PartialEq, Eq)]` enum with `to_raw()`
+///   and a `TryFrom<T>` that returns undeclared raw values as the error.
+///
+/// What the getter does with a raw value no variant declares is chosen in
+/// parentheses after `enum`:
+///
+/// | Syntax | Getter returns | Undeclared value |
+/// |--------|----------------|------------------|
+/// | `as enum Name` | `Name` | the first variant, as `Name::from_raw()` does |
+/// | `as enum(result) Name` | `Result<Name, T>` | `Err(raw)` |
+/// | `as enum(option) Name` | `Option<Name>` | `None` |
+/// | `as enum(unknown) Name` | `Name` | an added `Name::Unknown(raw)` variant |
+///
+/// Setters take `Name` in every case.
+///
+/// A signed field round-trips negative values through its two's-complement
+/// bits:
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use ddevmem::{register_map, DevMem};
+///
+/// register_map! {
+///     pub unsafe map Sensor (u32) {
+///         0x00 => rw trim: u32 {
+///             bias:   0..=4 as i8,   // -16..=15
+///             gain:   8..=15 as i8
+///         }
+///     }
+/// }
+///
+/// let devmem = Arc::new(unsafe { DevMem::new(0x4000_0000, Some(4)).unwrap() });
+/// let mut sensor = unsafe { Sensor::new(devmem).unwrap() };
+///
+/// sensor.set_trim_bias(-3);
+/// assert_eq!(sensor.trim(), 0b11101);
+/// assert_eq!(sensor.trim_bias(), -3);
+///
+/// sensor.write_trim_fields(|w| w.with_bias(15).with_gain(-128));
+/// assert_eq!(sensor.read_trim().gain(), -128);
+/// assert_eq!(sensor.trim_bias(), 15);
+///
+/// assert_eq!(sensor.try_set_trim_bias(16), Err(16));
+/// assert_eq!(sensor.try_set_trim_bias(-16), Ok(()));
+/// assert_eq!(sensor.trim_bias(), -16);
+/// assert_eq!(sensor.read_trim().try_with_bias(-17), Err(-17));
+///
+/// let overflow = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+///     sensor.set_trim_bias(16)
+/// }));
+/// assert!(overflow.is_err());
+/// ```
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use ddevmem::{register_map, DevMem};
+///
+/// register_map! {
+///     pub unsafe map Motor (u32) {
+///         0x00 => rw ctrl: u32 {
+///             state: 0..=1 as enum(result) State { Stopped = 0, Running = 1 },
+///             dir:   2..=3 as enum(unknown) Dir { Fwd = 0, Rev = 1 }
+///         }
+///     }
+/// }
+///
+/// let devmem = Arc::new(unsafe { DevMem::new(0x4000_0000, Some(4)).unwrap() });
+/// let mut motor = unsafe { Motor::new(devmem).unwrap() };
+///
+/// motor.set_ctrl_state(State::Running);
+/// assert_eq!(motor.ctrl_state(), Ok(State::Running));
+///
+/// motor.set_ctrl(0b1110); // state 2 and dir 3 are not declared
+/// assert_eq!(motor.ctrl_state(), Err(2));
+/// assert_eq!(motor.ctrl_dir(), Dir::Unknown(3));
+/// assert_eq!(State::try_from(1), Ok(State::Running));
+/// ```
+///
+/// ## Scaled bitfields
+///
+/// Integer fields can also declare a conversion to a physical value,
+/// `value = raw × scale + offset`, after the type:
+///
+/// - `field: lo..=hi scale 2.5 offset 100 unit "mV"` — `offset` and `unit`
+///   are optional; `scale` and `offset` may be any constant expression. A
+///   signed type (`as i8 scale 0.5`) makes `raw` two's complement.
+/// - `field: lo..=hi as fixed(Q4.8)` — two's-complement fixed point with
+///   4 integer bits (counting the sign bit) and 8 fractional ones;
+///   `fixed(UQ4.8)` is unsigned. The field must be exactly `m + n` bits
+///   wide. A `unit` may follow.
+///
+/// Besides the raw getter and setter, such a field gets `reg_field_f64()`,
+/// `reg_field_f32()`, `set_reg_field_f64(v)` and `set_reg_field_f32(v)`, and
+/// the value type gets `field_f64()` / `with_field_f64(v)` and their `f32`
+/// twins. The setters round to the nearest step and saturate at the
+/// field's range; NaN writes 0.
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use ddevmem::{register_map, DevMem};
+///
+/// register_map! {
+///     pub unsafe map Pmic (u32) {
+///         0x00 => rw buck: u32 {
+///             vout: 0..=7 scale 12.5 offset 600 unit "mV",
+///             gain: 8..=19 as fixed(Q4.8)
+///         }
+///     }
+/// }
+///
+/// let devmem = Arc::new(unsafe { DevMem::new(0x4000_0000, Some(4)).unwrap() });
+/// let mut pmic = unsafe { Pmic::new(devmem).unwrap() };
+///
+/// pmic.set_buck_vout_f64(1800.0);
+/// assert_eq!(pmic.buck_vout(), 96);
+/// pmic.set_buck_vout_f64(1806.0); // rounds to the nearest step
+/// assert_eq!(pmic.buck_vout_f64(), 1800.0);
+/// pmic.set_buck_vout_f64(5000.0); // saturates
+/// assert_eq!(pmic.buck_vout(), 255);
+///
+/// pmic.set_buck_gain_f32(-1.5);
+/// assert_eq!(pmic.buck_gain(), 0xE80);
+/// assert_eq!(pmic.read_buck().gain_f32(), -1.5);
+/// ```
 ///
 /// ## Register arrays
 ///
@@ -120,32 +312,375 @@ pub mod web;
 /// }
 /// ```
 ///
+/// ## Layout checks
+///
+/// The macro rejects registers that overlap (including array elements
+/// running into the next register), bitfields whose low bit is above their
+/// high bit or whose high bit is outside the register, and overlapping
+/// bitfields. Literal offsets and bit positions are checked by the macro
+/// itself; other expressions by `const` assertions. Either way the error
+/// points at the offending entry. `#[allow(overlap)]` on a register or field
+/// marks an intended alias:
+///
+/// ```rust,compile_fail
+/// # use ddevmem::register_map;
+/// register_map! {
+///     pub unsafe map Uart (u32) {
+///         0x00 => rw fifo: [u32; 4],
+///         0x08 => rw ctrl: u32  // error: register `ctrl` at 0x8..0xC overlaps `fifo`
+///     }
+/// }
+/// ```
+///
+/// ```rust
+/// # use ddevmem::register_map;
+/// register_map! {
+///     pub unsafe map Uart (u32) {
+///         0x00 => rw fifo: [u32; 4],
+///         0x08 => #[allow(overlap)] rw fifo_hi: u32,
+///         0x10 => rw ctrl: u32 {
+///             baud: 0..=7,
+///             #[allow(overlap)]
+///             baud_lo: 0..=3
+///         }
+///     }
+/// }
+/// ```
+///
+/// ## Access kinds
+///
+/// Besides `rw`, `ro` and `wo`, registers and fields can have semantics
+/// where a plain read-modify-write would do harm:
+///
+/// | Kind | Meaning | Generated |
+/// |------|---------|-----------|
+/// | `w1c` | write 1 to clear | getters; setters write only the target bits |
+/// | `w1s` | write 1 to set | getters; setters write only the target bits |
+/// | `wosc` | write 1 to trigger, self-clearing | setters write only the target bits |
+/// | `rc` | reading clears | getters, `#[must_use]` and documented as clearing |
+///
+/// A field of an `rw` register can override the kind by naming it before
+/// the field (`w1c overrun: 8`). Writes to the other fields of that register
+/// then write the write-one fields as 0, and `modify_*` hands them to the
+/// closure as 0, so updating `enable` does not clear a pending flag. Other
+/// registers cannot mix kinds.
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use ddevmem::{register_map, DevMem};
+///
+/// register_map! {
+///     pub unsafe map Irq (u32) {
+///         0x00 => w1c status: u32 {
+///             rx: 0 as bool,
+///             tx: 1 as bool
+///         },
+///         0x04 => rw ctrl: u32 {
+///             enable: 0 as bool,
+///             w1c overrun: 8 as bool
+///         },
+///         0x08 => rc events: u32
+///     }
+/// }
+///
+/// let devmem = Arc::new(unsafe { DevMem::new(0x4000_0000, Some(0x10)).unwrap() });
+/// devmem.write(0x00, 0b11u32).unwrap(); // rx and tx pending
+/// devmem.write(0x04, 0x100u32).unwrap(); // overrun pending
+/// let mut irq = unsafe { Irq::new(devmem.clone()).unwrap() };
+///
+/// // Only the rx bit is written; writing the whole value back would clear tx.
+/// irq.set_status_rx(true);
+/// assert_eq!(devmem.read::<u32>(0x00), Some(0b01));
+///
+/// // The read-modify-write writes `overrun` as 0, leaving it pending.
+/// irq.set_ctrl_enable(true);
+/// assert_eq!(devmem.read::<u32>(0x04), Some(0x001));
+/// ```
+///
+/// `reset_<reg>()` is generated for `rw` and `wo` registers only, and
+/// `write_<reg>_fields` on a write-one register starts from zero.
+///
+/// ## Reset values
+///
+/// `= value` after the type declares the register's reset value (every
+/// element for arrays); undeclared registers reset to `0`.
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use ddevmem::{register_map, DevMem};
+///
+/// register_map! {
+///     pub unsafe map Uart (u32) {
+///         0x00 => rw control: u32 = 0x0000_0010 {
+///             enable: 0 as bool,
+///             baud:   4..=7
+///         },
+///         0x04 => ro status: u32 = 0x1,
+///         0x08 => rw scratch: [u32; 2] = 0xA5
+///     }
+/// }
+///
+/// // The emulator starts with the declared values instead of zeros.
+/// let devmem = Arc::new(unsafe { DevMem::new(0x4000_0000, Some(0x10)).unwrap() });
+/// let mut uart = unsafe { Uart::new(devmem).unwrap() };
+/// assert_eq!(uart.control(), Uart::CONTROL_RESET);
+/// assert_eq!(uart.status(), 0x1);
+/// assert!(uart.is_reset());
+///
+/// uart.set_control_enable(true);
+/// uart.set_scratch(1, 0);
+/// assert!(!uart.is_reset());
+///
+/// uart.reset_all();
+/// assert!(uart.is_reset());
+/// assert_eq!(uart.scratch(1), 0xA5);
+/// ```
+///
+/// ## Backends
+///
+/// The generated struct is generic over the [`Backend`] of its [`DevMem`]
+/// (`Regs<B: Backend = DefaultBackend>`), so the same map can wrap real
+/// hardware, the emulator, or a custom backend. `B` is inferred from the
+/// `Arc<DevMem<B>>` passed to `new()`.
+///
 /// # Generated API
 ///
 /// For a register named `ctrl` the following methods are generated:
 ///
 /// | Kind | Method | Signature |
 /// |------|--------|-----------|
+/// | map  | `new(devmem)` | `unsafe fn(Arc<DevMem<B>>) -> Result<Self, Error>` |
+/// | map  | `claim(devmem)` | `fn(Arc<DevMem<B>>) -> Result<Self, Error>` |
+/// | map  | `open(base)` | `unsafe fn(usize) -> Result<Self, Error>` |
+/// | map  | `REGION_SIZE` | `const usize` — offset `0` to the end of the last register |
+/// | map  | `reset_all()` | `fn(&mut self)` — writes `RESET` to every `rw` register |
+/// | map  | `is_reset()` | `fn(&self) -> bool` — every `rw` register reads `RESET` |
 /// | all  | `ctrl_offset()` | `fn(&self) -> usize` |
 /// | all  | `ctrl_address()` | `fn(&self) -> usize` |
-/// | `rw` / `ro` | `ctrl()` | `fn(&self) -> T` |
-/// | `rw` / `wo` | `set_ctrl(value)` | `fn(&mut self, T)` |
+/// | readable | `ctrl()` | `fn(&self) -> T` |
+/// | writable | `set_ctrl(value)` | `fn(&mut self, T)` |
+/// | `rw` / `wo` | `reset_ctrl()` | `fn(&mut self)` — every element for arrays |
 /// | `rw` | `modify_ctrl(f)` | `fn(&mut self, FnOnce(T) -> T)` |
 ///
 /// For a bitfield `enable` on register `ctrl`:
 ///
 /// | Kind | Method | Signature |
 /// |------|--------|-----------|
-/// | `rw` / `ro` | `ctrl_enable()` | `fn(&self) -> T` |
-/// | `rw` / `wo` | `set_ctrl_enable(value)` | `fn(&mut self, T)` |
+/// | readable | `ctrl_enable()` | `fn(&self) -> T` |
+/// | writable | `set_ctrl_enable(value)` | `fn(&mut self, T)` |
+///
+/// Readable kinds are `rw`, `ro`, `w1c`, `w1s` and `rc`; writable kinds are
+/// `rw`, `wo`, `w1c`, `w1s` and `wosc`.
 ///
 /// When a type suffix is present, `T` becomes the specified type (`bool`,
-/// `u8`, or the generated enum).
+/// `u8`, or the generated enum). [Scaled](#scaled-bitfields) fields also get
+/// `ctrl_enable_f64()` / `set_ctrl_enable_f64(v)` and `f32` variants.
+///
+/// Offsets, array lengths and field masks are also generated as associated
+/// constants, usable in `const` items and match arms:
+///
+/// | Constant | Type | Value |
+/// |----------|------|-------|
+/// | `CTRL_OFFSET` | `usize` | offset of `ctrl` (element 0 for arrays) |
+/// | `FIFO_LEN` | `usize` | element count of an array register `fifo` |
+/// | `CTRL_RESET` | `T` | declared reset value of `ctrl`, else `0` |
+/// | `CTRL_ENABLE_MASK` | `T` | mask of the field, shifted into place |
+/// | `CTRL_ENABLE_SHIFT` | `u32` | bit position of the field |
+///
+/// The same values live in a module named after the map in snake case, with
+/// one submodule per register (`regs::ctrl::OFFSET`, `regs::ctrl::RESET`,
+/// `regs::ctrl::ENABLE_MASK`, …), for code that builds raw values without
+/// a map instance. The expressions are evaluated once, in the associated
+/// constants, so offsets and bit positions may name any const in scope where
+/// the map is declared; the module re-exports the results. In a map
+/// declared inside a function body they have to be literals, since the
+/// module cannot reach items of the function, the map struct included.
+///
+/// ```rust
+/// use ddevmem::register_map;
+///
+/// register_map! {
+///     pub unsafe map DmaRegs (u32) {
+///         0x00 => rw ctrl: u32 {
+///             run:  0 as bool,
+///             mode: 1..=3
+///         },
+///         0x10 => rw desc: [u32; 4]
+///     }
+/// }
+///
+/// const CTRL: usize = DmaRegs::CTRL_OFFSET;
+/// assert_eq!(CTRL, 0x00);
+/// assert_eq!(DmaRegs::DESC_LEN, 4);
+/// assert_eq!(DmaRegs::CTRL_MODE_MASK, 0b1110);
+/// assert_eq!(DmaRegs::CTRL_MODE_SHIFT, 1);
+///
+/// let raw = dma_regs::ctrl::RUN_MASK | (2 << dma_regs::ctrl::MODE_SHIFT);
+/// assert_eq!(raw, 0b101);
+///
+/// let name = match 0x10 {
+///     DmaRegs::CTRL_OFFSET => "ctrl",
+///     dma_regs::desc::OFFSET => "desc",
+///     _ => "?",
+/// };
+/// assert_eq!(name, "desc");
+/// ```
+///
+/// The map module is evaluated outside any enclosing function, so offsets and
+/// `as` types must be nameable from the module that invokes the macro. Field
+/// enums are defined in the map module and re-exported next to the map.
+///
+/// `new()` and `claim()` validate every register against
+/// [`DevMem::len`] and name the first one that does not fit:
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use ddevmem::{register_map, DevMem};
+///
+/// register_map! {
+///     pub unsafe map Big (u32) {
+///         0x00 => rw ctrl: u32,
+///         0x40 => rw fifo: [u32; 4]
+///     }
+/// }
+///
+/// let devmem = Arc::new(unsafe { DevMem::new(0x4000_0000, Some(0x48)).unwrap() });
+/// let err = unsafe { Big::new(devmem) }.err().unwrap();
+/// assert_eq!(err.to_string(), "register `fifo` at 0x40..0x50 exceeds region of 0x48 bytes");
+/// ```
+///
+/// `REGION_SIZE` is the span the map needs, and `open(base)` maps exactly
+/// that much with [`DevMem::new`]:
+///
+/// ```rust
+/// # use ddevmem::register_map;
+/// # register_map! {
+/// #     pub unsafe map Big (u32) {
+/// #         0x00 => rw ctrl: u32,
+/// #         0x40 => rw fifo: [u32; 4]
+/// #     }
+/// # }
+/// const _: () = assert!(Big::REGION_SIZE == 0x50);
+///
+/// let mut big = unsafe { Big::open(0x4000_0000).unwrap() };
+/// big.set_fifo(3, 0xAB);
+/// assert_eq!(big.fifo_address(3), 0x4000_004C);
+/// ```
+///
+/// # Register values
+///
+/// Every bitfield setter is its own read-modify-write. To change several
+/// fields at once, registers with bitfields also get a value type in the map
+/// module (`ctrl` → `CtrlValue`) with a getter and a builder-style `with_*`
+/// setter per field:
+///
+/// | Kind | Method | Signature |
+/// |------|--------|-----------|
+/// | readable | `read_ctrl()` | `fn(&self) -> CtrlValue` |
+/// | writable | `write_ctrl(value)` | `fn(&mut self, CtrlValue)` |
+/// | writable | `write_ctrl_fields(f)` | `fn(&mut self, FnOnce(w) -> CtrlValue)` |
+/// | `rw` | `modify_ctrl_fields(f)` | `fn(&mut self, FnOnce(r, w) -> CtrlValue)` |
+///
+/// `modify_ctrl_fields` reads once, hands the value to `f` both as `r` (to
+/// inspect) and `w` (to build on), and writes the result back once, so no
+/// intermediate combination of fields reaches the hardware:
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use ddevmem::{register_map, DevMem};
+///
+/// register_map! {
+///     pub unsafe map Pll (u32) {
+///         0x00 => rw ctrl: u32 {
+///             enable: 0 as bool,
+///             div:    4..=7 as u8,
+///             src:    8..=9 as enum PllSrc { Xtal = 0, Ext = 1, Osc = 2 }
+///         }
+///     }
+/// }
+///
+/// let mut pll = unsafe { Pll::open(0x4000_0000).unwrap() };
+///
+/// pll.modify_ctrl_fields(|r, w| w.with_enable(true).with_div(r.div() + 3).with_src(PllSrc::Ext));
+///
+/// let ctrl = pll.read_ctrl();
+/// assert!(ctrl.enable());
+/// assert_eq!(ctrl.div(), 3);
+/// assert_eq!(ctrl.src(), PllSrc::Ext);
+/// assert_eq!(ctrl.to_raw(), 0x131);
+/// assert_eq!(format!("{ctrl:?}"), "CtrlValue { enable: true, div: 3, src: Ext }");
+///
+/// pll.write_ctrl(pll::CtrlValue::default().with_div(9));
+/// assert_eq!(pll.ctrl(), 0x90);
+/// ```
+///
+/// `write_ctrl_fields` starts from the reset value (`CtrlValue::default()`)
+/// and never reads the register.
+///
+/// # Write-only registers
+///
+/// Reading a `wo` register can return garbage or trigger side effects, so
+/// nothing generated for one ever reads it. The map keeps a shadow copy of
+/// the last value written (the reset value initially). `set_*` field
+/// setters merge into that copy and write it out:
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use ddevmem::{register_map, DevMem};
+///
+/// register_map! {
+///     pub unsafe map Dma (u32) {
+///         0x00 => wo cmd: u32 {
+///             start:   0 as bool,
+///             channel: 4..=7
+///         }
+///     }
+/// }
+///
+/// let devmem = Arc::new(unsafe { DevMem::new(0x4000_0000, Some(4)).unwrap() });
+/// devmem.write(0, 0xFFFF_FFFFu32).unwrap(); // what a read would return
+/// let mut dma = unsafe { Dma::new(devmem.clone()).unwrap() };
+///
+/// dma.set_cmd_channel(3);
+/// assert_eq!(devmem.read::<u32>(0), Some(0x30)); // garbage not merged back
+/// dma.set_cmd_start(true);
+/// assert_eq!(devmem.read::<u32>(0), Some(0x31));
+///
+/// dma.write_cmd_fields(|w| w.with_channel(5).with_start(true));
+/// assert_eq!(devmem.read::<u32>(0), Some(0x51));
+/// ```
+///
 /// # Safety
 ///
-/// The macro-generated `new()` is `unsafe` because [`DevMem`] does not track
-/// which regions are claimed by register maps. The caller must ensure no
-/// overlapping maps alias the same memory.
+/// The macro-generated `new()` is `unsafe` because it bypasses the claim
+/// registry: the caller must ensure no overlapping maps alias the same
+/// memory.
+///
+/// The safe alternative is `claim()`, which reserves the map's registers with
+/// [`DevMem::claim`] and fails with [`Error::RegionClaimed`] while another
+/// live map holds an overlapping range. The claim is released on drop:
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use ddevmem::{register_map, DevMem};
+///
+/// register_map! {
+///     pub unsafe map Gpio (u32) {
+///         0x00 => rw out: u32,
+///         0x04 => rw dir: u32
+///     }
+/// }
+///
+/// let devmem = Arc::new(unsafe { DevMem::new(0x4000_0000, Some(0x100)).unwrap() });
+/// let gpio = Gpio::claim(devmem.clone()).unwrap();
+/// assert!(Gpio::claim(devmem.clone()).is_err());
+///
+/// drop(gpio);
+/// assert!(Gpio::claim(devmem).is_ok());
+/// ```
+///
+/// Claims are shared between a [`DevMem`] and its [`slice`](DevMem::slice)s,
+/// so one window can be split between several safely claimed maps.
 ///
 /// # Documentation
 ///
@@ -0,0 +1,316 @@
+//! Physical address allowlist / denylist.
+//!
+//! A typo in a base address can map RAM the kernel is using. An
+//! [`AddressPolicy`] lists the physical ranges a process may map; the
+//! `DevMem` constructors that map physical memory ([`DevMem::new`],
+//! [`DevMemBuilder::build`], [`DevMem::from_file`] / [`from_fd`]) check it
+//! before calling `mmap` and fail with [`Error::AddressForbidden`].
+//!
+//! A policy is installed process-wide with [`AddressPolicy::set_global`] or
+//! per builder with [`DevMemBuilder::policy`]. Without one, every address is
+//! permitted.
+//!
+//! * With no `allow` ranges, everything outside the `deny` ranges is
+//!   permitted.
+//! * With `allow` ranges, a mapping must lie entirely inside one of them
+//!   and must not overlap any `deny` range.
+//!
+//! ```rust
+//! use ddevmem::{policy::AddressPolicy, DevMem, Error};
+//!
+//! let path = std::env::temp_dir().join("ddevmem-policy-doctest.bin");
+//! std::fs::write(&path, [0u8; 0x2000]).unwrap();
+//!
+//! let policy = AddressPolicy::new().allow(0x1000, 0x1000);
+//! let map = |address: usize| unsafe {
+//!     DevMem::builder()
+//!         .path(&path)
+//!         .offset(address as u64)
+//!         .len(0x100)
+//!         .policy(policy.clone())
+//!         .build()
+//! };
+//!
+//! assert!(map(0x1000).is_ok());
+//! assert!(matches!(map(0x0F80), Err(Error::AddressForbidden { address: 0x0F80, .. })));
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+//!
+//! # Policy file
+//!
+//! [`AddressPolicy::load`] reads one directive per line; `#` starts a
+//! comment and numbers are hex (`0x`) or decimal:
+//!
+//! ```text
+//! allow 0x40000000 0x10000000     # FPGA fabric
+//! deny  0x43C10000 0x1000         # except the clock wizard
+//! deny-system-ram                 # everything /proc/iomem calls System RAM
+//! deny-system-ram /tmp/iomem      # same, from another file
+//! ```
+//!
+//! [`DevMem::new`]: crate::DevMem::new
+//! [`DevMem::from_file`]: crate::DevMem::from_file
+//! [`from_fd`]: crate::DevMem::from_fd
+//! [`DevMemBuilder::build`]: crate::DevMemBuilder::build
+//! [`DevMemBuilder::policy`]: crate::DevMemBuilder::policy
+
+use std::{
+    fs, io,
+    ops::Range,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use crate::Error;
+
+/// Policy installed by [`AddressPolicy::set_global`].
+static GLOBAL: RwLock<Option<Arc<AddressPolicy>>> = RwLock::new(None);
+
+/// Set of physical ranges a process may map; see the
+/// [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddressPolicy {
+    allow: Vec<Range<u64>>,
+    deny: Vec<Range<u64>>,
+}
+
+impl AddressPolicy {
+    /// Empty policy: everything is permitted until ranges are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Permits `len` bytes at `address`. Once any range is allowed, only
+    /// allowed ranges may be mapped.
+    pub fn allow(mut self, address: u64, len: u64) -> Self {
+        self.allow.push(address..address.saturating_add(len));
+        self
+    }
+
+    /// Forbids `len` bytes at `address`, even inside an allowed range.
+    pub fn deny(mut self, address: u64, len: u64) -> Self {
+        self.deny.push(address..address.saturating_add(len));
+        self
+    }
+
+    /// Denies every range `/proc/iomem` reports as `System RAM`.
+    ///
+    /// `/proc/iomem` shows zero addresses to unprivileged readers, which
+    /// would deny nothing; that is an error, so read it as root.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantReadFile`] if `/proc/iomem` cannot be read or
+    /// its addresses are hidden.
+    pub fn deny_system_ram(self) -> Result<Self, Error> {
+        self.deny_system_ram_from(crate::iomem::PROC_IOMEM)
+    }
+
+    /// [`deny_system_ram`](Self::deny_system_ram) reading an
+    /// iomem-formatted file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantReadFile`] if `path` cannot be read or parsed,
+    /// or if its addresses are hidden ([`Iomem::is_redacted`]).
+    ///
+    /// [`Iomem::is_redacted`]: crate::iomem::Iomem::is_redacted
+    pub fn deny_system_ram_from(mut self, path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let iomem = crate::iomem::parse(path)?;
+        if iomem.is_redacted() {
+            return Err(Error::CantReadFile {
+                path: path.to_owned(),
+                source: io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "addresses are hidden from unprivileged readers; read it as root",
+                ),
+            });
+        }
+        for ram in iomem.iter().filter(|r| r.name == crate::iomem::SYSTEM_RAM) {
+            self.deny.push(ram.start..ram.end.saturating_add(1));
+        }
+        Ok(self)
+    }
+
+    /// Reads a policy file; see the [module documentation](self) for the
+    /// format.
+    ///
+    /// ```rust
+    /// use std::fs;
+    /// use ddevmem::{policy::AddressPolicy, DevMem, Error};
+    ///
+    /// let dir = std::env::temp_dir().join("ddevmem-policy-load-doctest");
+    /// fs::create_dir_all(&dir).unwrap();
+    /// fs::write(
+    ///     dir.join("iomem"),
+    ///     "00000000-3fffffff : System RAM\n  00008000-00ffffff : Kernel code\n\
+    ///      40000000-4fffffff : fpga\n",
+    /// )
+    /// .unwrap();
+    /// fs::write(dir.join("policy"), format!("deny-system-ram {}\n", dir.join("iomem").display()))
+    ///     .unwrap();
+    ///
+    /// let policy = AddressPolicy::load(dir.join("policy")).unwrap();
+    /// assert!(!policy.permits(0x1000_0000, 4));
+    /// assert!(policy.permits(0x4000_0000, 0x1000));
+    ///
+    /// AddressPolicy::set_global(policy);
+    /// fs::write(dir.join("mem"), [0u8; 0x100]).unwrap();
+    /// let file = fs::File::open(dir.join("mem")).unwrap();
+    /// let err = unsafe { DevMem::from_file(file, 0x3FFF_FF00, 0x100) }.unwrap_err();
+    /// assert!(matches!(err, Error::AddressForbidden { address: 0x3FFF_FF00, len: 0x100 }));
+    /// # AddressPolicy::clear_global();
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantReadFile`] if the file (or an iomem file it
+    /// names) cannot be read or a line is malformed; the error names the
+    /// offending line.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let err = |source| Error::CantReadFile {
+            path: path.to_owned(),
+            source,
+        };
+        let text = fs::read_to_string(path).map_err(err)?;
+
+        let mut policy = Self::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = |msg: &str| {
+                err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {msg}", n + 1),
+                ))
+            };
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [kind @ ("allow" | "deny"), address, len] => {
+                    let address = parse_num(address).ok_or_else(|| invalid("bad address"))?;
+                    let len = parse_num(len).ok_or_else(|| invalid("bad length"))?;
+                    policy = if *kind == "allow" {
+                        policy.allow(address, len)
+                    } else {
+                        policy.deny(address, len)
+                    };
+                }
+                ["deny-system-ram"] => policy = policy.deny_system_ram()?,
+                ["deny-system-ram", iomem] => policy = policy.deny_system_ram_from(iomem)?,
+                _ => {
+                    return Err(invalid(
+                        "expected `allow ADDR LEN`, `deny ADDR LEN` or `deny-system-ram [PATH]`",
+                    ))
+                }
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Whether `len` bytes at `address` may be mapped.
+    pub fn permits(&self, address: u64, len: u64) -> bool {
+        let end = address.saturating_add(len.max(1));
+        let allowed = self.allow.is_empty()
+            || self
+                .allow
+                .iter()
+                .any(|r| r.start <= address && end <= r.end);
+        allowed && !self.deny.iter().any(|r| r.start < end && address < r.end)
+    }
+
+    /// [`permits`](Self::permits) as a `Result`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AddressForbidden`] if the range is not permitted.
+    pub fn check(&self, address: usize, len: usize) -> Result<(), Error> {
+        if self.permits(address as u64, len as u64) {
+            Ok(())
+        } else {
+            Err(Error::AddressForbidden { address, len })
+        }
+    }
+
+    /// Installs `policy` for every `DevMem` created afterwards in this
+    /// process (unless a builder sets its own).
+    pub fn set_global(policy: AddressPolicy) {
+        *GLOBAL.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(policy));
+    }
+
+    /// Removes the global policy.
+    pub fn clear_global() {
+        *GLOBAL.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// The global policy, if one is installed.
+    pub fn global() -> Option<Arc<AddressPolicy>> {
+        GLOBAL.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Checks `len` bytes at `address` against the global policy.
+pub(crate) fn check_global(address: usize, len: usize) -> Result<(), Error> {
+    AddressPolicy::global().map_or(Ok(()), |policy| policy.check(address, len))
+}
+
+/// Parses a hex (`0x`) or decimal number, allowing `_` separators.
+pub(crate) fn parse_num(text: &str) -> Option<u64> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(&hex.replace('_', ""), 16).ok(),
+        None => text.replace('_', "").parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iomem(name: &str, text: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("ddevmem-policy-{name}-{}", std::process::id()));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn system_ram_is_denied() {
+        let path = iomem(
+            "ram",
+            "00000000-3fffffff : System RAM\n  00008000-00ffffff : Kernel code\n\
+             40000000-4fffffff : fpga\n",
+        );
+        let policy = AddressPolicy::new().deny_system_ram_from(&path).unwrap();
+        assert!(!policy.permits(0x3FFF_FFFF, 1));
+        assert!(policy.permits(0x4000_0000, 0x1000));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn redacted_iomem_is_an_error() {
+        let path = iomem(
+            "redacted",
+            "00000000-00000000 : System RAM\n  00000000-00000000 : Kernel code\n",
+        );
+        match AddressPolicy::new().deny_system_ram_from(&path) {
+            Err(Error::CantReadFile { source, .. }) => {
+                assert_eq!(source.kind(), io::ErrorKind::PermissionDenied);
+            }
+            other => panic!("expected CantReadFile, got {other:?}"),
+        }
+
+        let file = iomem(
+            "redacted-policy",
+            &format!("deny-system-ram {}\n", path.display()),
+        );
+        assert!(matches!(
+            AddressPolicy::load(&file),
+            Err(Error::CantReadFile { .. })
+        ));
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&file).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+//! Claim registry handing out non-overlapping [`Region`] tokens.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// One live claim, in backend coordinates.
+struct Claim {
+    id: u64,
+    start: usize,
+    end: usize,
+    address: usize,
+    owner: &'static str,
+}
+
+#[derive(Default)]
+struct Claims {
+    next_id: u64,
+    live: Vec<Claim>,
+}
+
+/// Claims shared by a [`DevMem`](crate::DevMem) and all of its slices.
+#[derive(Clone, Default)]
+pub(crate) struct Registry(Arc<Mutex<Claims>>);
+
+/// The live claim a rejected request collided with.
+pub(crate) struct Conflict {
+    pub(crate) owner: &'static str,
+    pub(crate) address: usize,
+    pub(crate) len: usize,
+}
+
+impl Registry {
+    /// Claims backend bytes `start..start + len`, reported as physical
+    /// `address` on behalf of `owner`.
+    pub(crate) fn claim(
+        &self,
+        start: usize,
+        len: usize,
+        address: usize,
+        owner: &'static str,
+    ) -> Result<Region, Conflict> {
+        let end = start + len;
+        let mut claims = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(c) = claims.live.iter().find(|c| start < c.end && c.start < end) {
+            return Err(Conflict {
+                owner: c.owner,
+                address: c.address,
+                len: c.end - c.start,
+            });
+        }
+        let id = claims.next_id;
+        claims.next_id += 1;
+        claims.live.push(Claim {
+            id,
+            start,
+            end,
+            address,
+            owner,
+        });
+        Ok(Region {
+            registry: self.clone(),
+            id,
+            address,
+            len,
+            owner,
+        })
+    }
+}
+
+/// Exclusive claim on part of a [`DevMem`](crate::DevMem), obtained from
+/// [`DevMem::claim`](crate::DevMem::claim).
+///
+/// While a `Region` is alive no other claim overlapping it can be made
+/// through the same mapping (the `DevMem` it was claimed from and every
+/// [`slice`](crate::DevMem::slice) of it). Dropping the token releases the
+/// claim.
+pub struct Region {
+    registry: Registry,
+    id: u64,
+    address: usize,
+    len: usize,
+    owner: &'static str,
+}
+
+impl Region {
+    /// Physical address of the first claimed byte.
+    #[inline(always)]
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Length of the claim in bytes.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` when the claim covers zero bytes.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Name the claim was made under.
+    #[inline(always)]
+    pub fn owner(&self) -> &'static str {
+        self.owner
+    }
+}
+
+impl Drop for Region {
+    fn drop(&mut self) {
+        let mut claims = self.registry.0.lock().unwrap_or_else(|e| e.into_inner());
+        claims.live.retain(|c| c.id != self.id);
+    }
+}
+
+impl fmt::Debug for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Region({} @ {:#X}..{:#X})",
+            self.owner,
+            self.address,
+            self.address + self.len
+        )
+    }
+}
@@ -0,0 +1,534 @@
+//! Linux UIO (userspace I/O) devices.
+//!
+//! A UIO device `/dev/uioN` exposes one or more memory maps and an
+//! interrupt line. The maps are described under
+//! `/sys/class/uio/uioN/maps/mapM/{addr,size,offset}` and mapped by passing
+//! `M * page_size` as the file offset to `mmap`. Interrupts are delivered
+//! through the device file itself:
+//!
+//! * a 4-byte `read()` blocks until the next interrupt and returns the total
+//!   interrupt count;
+//! * a 4-byte `write()` of `1` re-enables the interrupt (for drivers such as
+//!   `uio_pdrv_genirq` that mask it in the kernel handler).
+//!
+//! [`Uio`] wraps both halves so a [`register_map!`](crate::register_map)
+//! can be paired with an interrupt-driven loop:
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use ddevmem::register_map;
+//! use ddevmem::uio::Uio;
+//!
+//! register_map! {
+//!     pub unsafe map Timer (u32) {
+//!         0x00 => rw ctrl: u32 { irq_en: 0 as bool },
+//!         0x04 => rw isr:  u32
+//!     }
+//! }
+//!
+//! let uio = Uio::open(0).unwrap();
+//! let devmem = unsafe { uio.map(0).unwrap() };
+//! let mut timer = unsafe { Timer::new(Arc::new(devmem)).unwrap() };
+//!
+//! timer.set_ctrl_irq_en(true);
+//! loop {
+//!     uio.enable_irq().unwrap();
+//!     let count = uio.wait_irq().unwrap();
+//!     let pending = timer.isr();
+//!     timer.set_isr(pending);
+//!     println!("interrupt #{count}: {pending:#X}");
+//! }
+//! ```
+//!
+//! The sysfs directory and device node can be given explicitly with
+//! [`Uio::open_path`], so a fake sysfs tree and a FIFO can stand in for a
+//! real device:
+//!
+//! ```rust
+//! use std::{fs, io::Write, process::Command};
+//! use ddevmem::uio::{Uio, UioMap};
+//!
+//! let root = std::env::temp_dir().join("ddevmem-uio-doctest");
+//! # let _ = fs::remove_dir_all(&root);
+//! let map0 = root.join("sys/maps/map0");
+//! fs::create_dir_all(&map0).unwrap();
+//! fs::write(root.join("sys/name"), "axi_timer\n").unwrap();
+//! fs::write(map0.join("addr"), "0x43c00000\n").unwrap();
+//! fs::write(map0.join("size"), "0x00010000\n").unwrap();
+//! fs::write(map0.join("offset"), "0x0\n").unwrap();
+//!
+//! let dev = root.join("uio0");
+//! assert!(Command::new("mkfifo").arg(&dev).status().unwrap().success());
+//!
+//! let uio = Uio::open_path(&dev, root.join("sys")).unwrap();
+//! assert_eq!(uio.name(), "axi_timer");
+//! assert_eq!(
+//!     uio.maps(),
+//!     &[UioMap { index: 0, addr: 0x43c0_0000, size: 0x1_0000, offset: 0 }]
+//! );
+//!
+//! // Simulate the kernel reporting interrupt #7.
+//! fs::OpenOptions::new().write(true).open(&dev).unwrap()
+//!     .write_all(&7u32.to_ne_bytes()).unwrap();
+//! assert_eq!(uio.wait_irq().unwrap(), 7);
+//! assert_eq!(uio.wait_irq_timeout(std::time::Duration::from_millis(10)).unwrap(), None);
+//! # fs::remove_dir_all(&root).unwrap();
+//! ```
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::unix::{
+        fs::OpenOptionsExt,
+        io::{AsRawFd, RawFd},
+    },
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{backend::Mmap, DevMem, Error};
+
+/// One memory map of a UIO device, as described in sysfs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UioMap {
+    /// Map index `M` (`mapM` in sysfs).
+    pub index: usize,
+    /// Page-aligned physical address of the map (`addr`).
+    pub addr: usize,
+    /// Size of the map in bytes from `addr`, so including `offset`
+    /// (`size`).
+    pub size: usize,
+    /// Offset of the device registers within the first page (`offset`,
+    /// `0` on kernels that do not export it).
+    pub offset: usize,
+}
+
+/// An open `/dev/uioN` device with its memory maps and interrupt.
+#[derive(Debug)]
+pub struct Uio {
+    file: File,
+    path: PathBuf,
+    name: String,
+    maps: Vec<UioMap>,
+}
+
+impl Uio {
+    /// Opens `/dev/uio{n}` and reads its description from
+    /// `/sys/class/uio/uio{n}`.
+    ///
+    /// # Errors
+    ///
+    /// See [`Uio::open_path`].
+    pub fn open(n: usize) -> Result<Self, Error> {
+        Self::open_path(format!("/dev/uio{n}"), format!("/sys/class/uio/uio{n}"))
+    }
+
+    /// Opens the device node `dev` and reads its description from the sysfs
+    /// directory `sysfs` (the one containing `name` and `maps/`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantOpenFile`] if `dev` cannot be opened, or
+    /// [`Error::CantReadFile`] if a sysfs attribute is missing or malformed.
+    pub fn open_path(dev: impl AsRef<Path>, sysfs: impl AsRef<Path>) -> Result<Self, Error> {
+        let dev = dev.as_ref();
+        let sysfs = sysfs.as_ref();
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(dev)
+            .map_err(|source| Error::CantOpenFile {
+                path: dev.to_owned(),
+                source,
+            })?;
+
+        let name = read_attr(&sysfs.join("name"))?;
+
+        let mut maps = Vec::new();
+        loop {
+            let dir = sysfs.join("maps").join(format!("map{}", maps.len()));
+            if !dir.is_dir() {
+                break;
+            }
+            let offset_path = dir.join("offset");
+            maps.push(UioMap {
+                index: maps.len(),
+                addr: read_hex(&dir.join("addr"))?,
+                size: read_hex(&dir.join("size"))?,
+                offset: if offset_path.exists() {
+                    read_hex(&offset_path)?
+                } else {
+                    0
+                },
+            });
+        }
+
+        Ok(Self {
+            file,
+            path: dev.to_owned(),
+            name,
+            maps,
+        })
+    }
+
+    /// Device node this instance was opened from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Driver-assigned name (sysfs `name`).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Memory maps exported by the device, in index order.
+    pub fn maps(&self) -> &[UioMap] {
+        &self.maps
+    }
+
+    /// Maps memory region `index` of the device.
+    ///
+    /// Offset `0` of the returned `DevMem` is the start of the device
+    /// registers, physical address [`UioMap::addr`] + [`UioMap::offset`],
+    /// and its length is [`UioMap::size`] − [`UioMap::offset`].
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`DevMem::new`], applied to the device memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CantOpenFile`] if `index` is not an exported map,
+    /// [`Error::OutOfBounds`] if its `offset` is not below its `size`, or
+    /// any error from [`DevMemBuilder::build`](crate::DevMemBuilder::build).
+    pub unsafe fn map(&self, index: usize) -> Result<DevMem<Mmap>, Error> {
+        let map = self.maps.get(index).ok_or_else(|| Error::CantOpenFile {
+            path: self.path.clone(),
+            source: io::Error::new(io::ErrorKind::NotFound, format!("no UIO map {index}")),
+        })?;
+        let out_of_bounds = Error::OutOfBounds {
+            offset: map.offset,
+            size: 1,
+            len: map.size,
+        };
+        if map.offset >= map.size {
+            return Err(out_of_bounds);
+        }
+        // The kernel maps whole pages from `addr`; the registers start
+        // `offset` bytes in.
+        let page = page_size::get();
+        let pages = DevMem::builder()
+            .path(&self.path)
+            .offset((index * page) as u64)
+            .len(map.size)
+            .address(map.addr)
+            .build()?;
+        pages
+            .slice(map.offset, map.size - map.offset)
+            .ok_or(out_of_bounds)
+    }
+
+    /// Re-enables the interrupt by writing `1` to the device.
+    ///
+    /// Drivers such as `uio_pdrv_genirq` disable the interrupt in their
+    /// kernel handler; call this before every wait.
+    pub fn enable_irq(&self) -> io::Result<()> {
+        self.set_irq(true)
+    }
+
+    /// Masks (`false`) or unmasks (`true`) the interrupt, if the UIO driver
+    /// supports it.
+    pub fn set_irq(&self, enabled: bool) -> io::Result<()> {
+        (&self.file).write_all(&(enabled as u32).to_ne_bytes())
+    }
+
+    /// Blocks until the next interrupt and returns the total interrupt count
+    /// reported by the kernel.
+    pub fn wait_irq(&self) -> io::Result<u32> {
+        loop {
+            if let Some(count) = self.wait_irq_inner(None)? {
+                return Ok(count);
+            }
+        }
+    }
+
+    /// Like [`wait_irq`](Self::wait_irq), but gives up after `timeout` and
+    /// returns `Ok(None)`. Signals arriving meanwhile do not cut the wait
+    /// short.
+    pub fn wait_irq_timeout(&self, timeout: Duration) -> io::Result<Option<u32>> {
+        // An unrepresentable deadline is as good as none.
+        self.wait_irq_inner(Instant::now().checked_add(timeout))
+    }
+
+    /// Asynchronous [`wait_irq`](Self::wait_irq) on the current Tokio
+    /// runtime.
+    ///
+    /// ```rust,no_run
+    /// # async fn run(uio: ddevmem::uio::Uio) {
+    /// loop {
+    ///     uio.enable_irq().unwrap();
+    ///     let count = uio.wait_irq_async().await.unwrap();
+    ///     println!("interrupt #{count}");
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn wait_irq_async(&self) -> io::Result<u32> {
+        use tokio::io::{unix::AsyncFd, Interest};
+
+        // SAFETY: the fd belongs to `self.file`, which outlives the borrow of
+        // `self` held by this future and therefore the `AsyncFd`.
+        let fd = unsafe {
+            AsyncFd::register_with_interest(Fd(self.file.as_raw_fd()), Interest::READABLE)?
+        };
+        loop {
+            let mut guard = fd.readable().await?;
+            match guard.try_io(|_| self.read_count()) {
+                Ok(result) => return result,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Polls until `deadline` (`None` = forever) and reads the count,
+    /// polling again with the time left after a signal or a spurious wakeup.
+    fn wait_irq_inner(&self, deadline: Option<Instant>) -> io::Result<Option<u32>> {
+        let mut pfd = libc::pollfd {
+            fd: self.file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        loop {
+            let timeout_ms = match deadline {
+                None => -1,
+                Some(deadline) => {
+                    // Round up so the wait never ends before the deadline.
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    let ms = left.as_nanos().div_ceil(1_000_000);
+                    ms.min(libc::c_int::MAX as u128) as libc::c_int
+                }
+            };
+            // SAFETY: `pfd` is a valid pollfd for the duration of the call.
+            let ready = unsafe { libc::poll(&mut pfd, 1, timeout_ms) };
+            match ready {
+                0 => return Ok(None),
+                n if n < 0 => {
+                    let err = io::Error::last_os_error();
+                    if err.kind() != io::ErrorKind::Interrupted {
+                        return Err(err);
+                    }
+                }
+                _ => match self.read_count() {
+                    Ok(count) => return Ok(Some(count)),
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(err) => return Err(err),
+                },
+            }
+        }
+    }
+
+    fn read_count(&self) -> io::Result<u32> {
+        let mut buf = [0u8; 4];
+        (&self.file).read_exact(&mut buf)?;
+        Ok(u32::from_ne_bytes(buf))
+    }
+}
+
+impl AsRawFd for Uio {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+/// Non-owning fd handed to `AsyncFd`; the `File` in [`Uio`] keeps it open.
+#[cfg(feature = "async")]
+struct Fd(RawFd);
+
+#[cfg(feature = "async")]
+impl AsRawFd for Fd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+fn read_attr(path: &Path) -> Result<String, Error> {
+    std::fs::read_to_string(path)
+        .map(|s| s.trim().to_owned())
+        .map_err(|source| Error::CantReadFile {
+            path: path.to_owned(),
+            source,
+        })
+}
+
+fn read_hex(path: &Path) -> Result<usize, Error> {
+    let text = read_attr(path)?;
+    let digits = text.strip_prefix("0x").unwrap_or(&text);
+    usize::from_str_radix(digits, 16).map_err(|err| Error::CantReadFile {
+        path: path.to_owned(),
+        source: io::Error::new(io::ErrorKind::InvalidData, format!("{text:?}: {err}")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Fake sysfs tree and device file under a fresh temp directory.
+    fn fixture(name: &str, maps: &[(usize, usize, usize)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("ddevmem-uio-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sys")).unwrap();
+        fs::write(root.join("sys/name"), "fixture\n").unwrap();
+        for (i, (addr, size, offset)) in maps.iter().enumerate() {
+            let dir = root.join(format!("sys/maps/map{i}"));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("addr"), format!("{addr:#x}\n")).unwrap();
+            fs::write(dir.join("size"), format!("{size:#x}\n")).unwrap();
+            fs::write(dir.join("offset"), format!("{offset:#x}\n")).unwrap();
+        }
+        fs::write(root.join("uio0"), vec![0u8; maps.len() * page_size::get()]).unwrap();
+        root
+    }
+
+    #[test]
+    fn map_with_nonzero_offset_starts_at_the_registers() {
+        let page = page_size::get();
+        let root = fixture(
+            "offset",
+            &[(0x43c0_0000, page, 0), (0x43c1_0000, page, 0x400)],
+        );
+        let mut dev = fs::read(root.join("uio0")).unwrap();
+        dev[page + 0x400..page + 0x404].copy_from_slice(&0xCAFE_F00Du32.to_ne_bytes());
+        fs::write(root.join("uio0"), dev).unwrap();
+
+        let uio = Uio::open_path(root.join("uio0"), root.join("sys")).unwrap();
+        let devmem = unsafe { uio.map(1).unwrap() };
+        assert_eq!(devmem.address(), 0x43c1_0400);
+        assert_eq!(devmem.len(), page - 0x400);
+        assert_eq!(devmem.read::<u32>(0), Some(0xCAFE_F00D));
+        assert_eq!(devmem.read::<u32>(page - 0x400), None);
+
+        let first = unsafe { uio.map(0).unwrap() };
+        assert_eq!((first.address(), first.len()), (0x43c0_0000, page));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn map_rejects_bad_index_and_offset() {
+        let root = fixture("bad", &[(0x43c0_0000, 0x100, 0x100)]);
+        let uio = Uio::open_path(root.join("uio0"), root.join("sys")).unwrap();
+        assert!(matches!(
+            unsafe { uio.map(0) },
+            Err(Error::OutOfBounds { offset: 0x100, .. })
+        ));
+        assert!(matches!(
+            unsafe { uio.map(1) },
+            Err(Error::CantOpenFile { .. })
+        ));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn open_path_reports_missing_and_malformed_attributes() {
+        let root = fixture("attrs", &[(0x43c0_0000, 0x1000, 0)]);
+        let open = || Uio::open_path(root.join("uio0"), root.join("sys"));
+
+        fs::remove_file(root.join("sys/maps/map0/offset")).unwrap();
+        assert_eq!(open().unwrap().maps()[0].offset, 0);
+
+        fs::write(root.join("sys/maps/map0/size"), "0xzz\n").unwrap();
+        match open() {
+            Err(Error::CantReadFile { path, source }) => {
+                assert!(path.ends_with("maps/map0/size"));
+                assert_eq!(source.kind(), io::ErrorKind::InvalidData);
+            }
+            other => panic!("expected CantReadFile, got {other:?}"),
+        }
+
+        fs::remove_file(root.join("sys/name")).unwrap();
+        assert!(matches!(open(), Err(Error::CantReadFile { path, .. }) if path.ends_with("name")));
+
+        let missing = Uio::open_path(root.join("uio1"), root.join("sys"));
+        assert!(matches!(missing, Err(Error::CantOpenFile { .. })));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn irq_counts_are_read_from_the_device() {
+        let root = fixture("irq", &[(0x43c0_0000, 0x1000, 0)]);
+        // A FIFO stands in for the device node: whatever is written to it
+        // comes back as the interrupt count.
+        let fifo = root.join("uio-fifo");
+        let c_path = std::ffi::CString::new(fifo.as_os_str().as_encoded_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+        let uio = Uio::open_path(&fifo, root.join("sys")).unwrap();
+
+        assert_eq!(
+            uio.wait_irq_timeout(Duration::from_millis(10)).unwrap(),
+            None
+        );
+
+        let mut kernel = OpenOptions::new().write(true).open(&fifo).unwrap();
+        kernel.write_all(&7u32.to_ne_bytes()).unwrap();
+        assert_eq!(
+            uio.wait_irq_timeout(Duration::from_secs(1)).unwrap(),
+            Some(7)
+        );
+
+        uio.enable_irq().unwrap();
+        assert_eq!(uio.wait_irq().unwrap(), 1);
+        uio.set_irq(false).unwrap();
+        assert_eq!(uio.wait_irq().unwrap(), 0);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn signals_do_not_end_a_timed_wait_early() {
+        extern "C" fn ignore(_: libc::c_int) {}
+        // No SA_RESTART, and poll() is never restarted anyway: the signal
+        // makes it fail with EINTR.
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = ignore as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            assert_eq!(
+                libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut()),
+                0
+            );
+        }
+
+        let root = fixture("eintr", &[(0x43c0_0000, 0x1000, 0)]);
+        let fifo = root.join("uio-fifo");
+        let c_path = std::ffi::CString::new(fifo.as_os_str().as_encoded_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+        let uio = Uio::open_path(&fifo, root.join("sys")).unwrap();
+        let mut kernel = OpenOptions::new().write(true).open(&fifo).unwrap();
+
+        let waiter = unsafe { libc::pthread_self() };
+        let count = std::thread::scope(|s| {
+            s.spawn(|| {
+                std::thread::sleep(Duration::from_millis(50));
+                assert_eq!(unsafe { libc::pthread_kill(waiter, libc::SIGUSR1) }, 0);
+                std::thread::sleep(Duration::from_millis(50));
+                kernel.write_all(&3u32.to_ne_bytes()).unwrap();
+            });
+            uio.wait_irq_timeout(Duration::from_secs(5)).unwrap()
+        });
+        assert_eq!(count, Some(3));
+
+        let start = Instant::now();
+        let waiter = unsafe { libc::pthread_self() };
+        let count = std::thread::scope(|s| {
+            s.spawn(|| {
+                std::thread::sleep(Duration::from_millis(20));
+                assert_eq!(unsafe { libc::pthread_kill(waiter, libc::SIGUSR1) }, 0);
+            });
+            uio.wait_irq_timeout(Duration::from_millis(200)).unwrap()
+        });
+        assert_eq!(count, None);
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -146,10 +146,32 @@ pub struct BitfieldInfo {
     pub lo: u32,
     /// High bit index (inclusive).
     pub hi: u32,
-    /// Type hint: `"raw"`, `"bool"`, `"u8"`, or an enum name.
+    /// Access kind, the register's unless the field overrides it; see
+    /// [`RegisterInfo::access`].
+    pub access: &'static str,
+    /// Type hint: `"raw"`, `"bool"`, `"u8"`, `"i8"`, a fixed-point format
+    /// such as `"Q4.8"`, or an enum name.
     pub field_type: &'static str,
+    /// Whether the field is two's complement (`as i8`, `as fixed(Q4.8)`
+    /// etc.), so values are shown and entered as signed numbers.
+    pub signed: bool,
+    /// For scaled fields (`scale 2.5`, `as fixed(Q4.8)`), the physical value
+    /// of one step; the UI shows `value × scale + offset`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+    /// Added to scaled values (0 unless the field declares an `offset`).
+    pub offset: f64,
+    /// Unit of the scaled value, if declared (`unit "mV"`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<&'static str>,
     /// Enum/bool variants (empty for plain integer fields).
     pub variants: Vec<VariantInfo>,
+    /// For enum fields, what the getter returns for a value no variant
+    /// declares: `"first"` (the first variant), `"result"` (`Err(raw)`),
+    /// `"option"` (`None`) or `"unknown"` (`Unknown(raw)`). The UI flags
+    /// such values.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decode: Option<&'static str>,
 }
 
 /// Description of a single register in the map.
@@ -164,10 +186,15 @@ pub struct RegisterInfo {
     pub doc: &'static str,
     /// Byte offset from the base address.
     pub offset: usize,
-    /// Access kind: `"rw"`, `"ro"`, or `"wo"`.
+    /// Access kind: `"rw"`, `"ro"`, `"wo"`, `"w1c"` (write 1 to clear),
+    /// `"w1s"` (write 1 to set), `"rc"` (read clears) or `"wosc"` (write 1
+    /// to trigger, self-clearing).
     pub access: &'static str,
     /// Width of the register value in bits (e.g. 32).
     pub width: usize,
+    /// Declared reset value, if the map gives one (`= 0x10` after the type).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reset: Option<u64>,
     /// Bitfields declared within this register.
     pub bitfields: Vec<BitfieldInfo>,
 }
@@ -193,8 +220,18 @@ pub trait RegisterMapInfo {
     /// Read a register at the given byte offset, returning its value as `u64`.
     fn read_register(&self, offset: usize) -> Option<u64>;
 
-    /// Write a register at the given byte offset from a `u64` value.
+    /// Write a register at the given byte offset from a `u64` value. The
+    /// shadow copy of a `wo` register is updated too, so its field setters
+    /// start from the value written here.
     fn write_register(&mut self, offset: usize, value: u64) -> Option<()>;
+
+    /// Last value written to the `wo` register at the given byte offset,
+    /// which the map keeps instead of reading the register back; `None` for
+    /// any other offset.
+    fn shadow_register(&self, offset: usize) -> Option<u64> {
+        let _ = offset;
+        None
+    }
 }
 
 // ─── Internal serialization helpers ──────────────────────────────────────────
@@ -210,6 +247,12 @@ struct RegisterMapDescription {
 #[derive(Deserialize)]
 struct ReadReq {
     offset: usize,
+    /// Read even though reading clears bits of the register.
+    #[serde(default)]
+    side_effects: bool,
+    /// Return the shadow copy of a `wo` register instead of reading it.
+    #[serde(default)]
+    shadow: bool,
 }
 
 #[derive(Serialize)]
@@ -454,8 +497,15 @@ impl WebUi {
     /// - `GET /` — single HTML page showing all maps
     /// - `GET /api/maps` — `{ title?: String, maps: [{ slug, name }, ...] }`
     /// - `GET /api/{slug}/info` — register metadata (name, base, registers)
-    /// - `POST /api/{slug}/read` — body `{ offset }`, returns `{ value }`
+    /// - `POST /api/{slug}/read` — body `{ offset, side_effects?, shadow? }`,
+    ///   returns `{ value }`
     /// - `POST /api/{slug}/write` — body `{ offset, value }`, returns `200 OK`
+    ///
+    /// Both only reach declared registers (`404` otherwise) and honour their
+    /// access kind with `403`: `wo` and `wosc` registers are never read,
+    /// `ro` and `rc` registers never written, and a register whose read
+    /// clears bits is read only with `side_effects: true`. `shadow: true`
+    /// returns the shadow copy of a `wo` register without touching the bus.
     pub fn build(self) -> Router {
         let state = WebUiState {
             maps:  self.maps,
@@ -530,6 +580,22 @@ fn find_map<'a>(maps: &'a [MapHandle], slug: &str) -> Result<&'a DynMap, StatusC
         .ok_or(StatusCode::NOT_FOUND)
 }
 
+/// The register declared at `offset`; the API reaches nothing else.
+fn register_at(
+    regs: &(dyn RegisterMapInfo + Send),
+    offset: usize,
+) -> Result<RegisterInfo, StatusCode> {
+    regs.registers()
+        .into_iter()
+        .find(|reg| reg.offset == offset)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Whether reading `reg` clears it or one of its fields.
+fn reads_clear(reg: &RegisterInfo) -> bool {
+    reg.access == "rc" || reg.bitfields.iter().any(|bf| bf.access == "rc")
+}
+
 async fn api_info(
     State(state): State<WebUiState>,
     axum::extract::Path(slug): axum::extract::Path<String>,
@@ -551,7 +617,18 @@ async fn api_read(
 ) -> Result<Json<ReadResp>, StatusCode> {
     let regs = find_map(&state.maps, &slug)?;
     let regs = regs.lock().await;
-    regs.read_register(req.offset)
+    let reg = register_at(&*regs, req.offset)?;
+    let value = if req.shadow {
+        regs.shadow_register(req.offset)
+    } else if matches!(reg.access, "wo" | "wosc") {
+        // Returns garbage or triggers the register's write side.
+        return Err(StatusCode::FORBIDDEN);
+    } else if reads_clear(&reg) && !req.side_effects {
+        return Err(StatusCode::FORBIDDEN);
+    } else {
+        regs.read_register(req.offset)
+    };
+    value
         .map(|value| Json(ReadResp { value }))
         .ok_or(StatusCode::BAD_REQUEST)
 }
@@ -563,6 +640,9 @@ async fn api_write(
 ) -> Result<StatusCode, StatusCode> {
     let regs = find_map(&state.maps, &slug)?;
     let mut regs = regs.lock().await;
+    if matches!(register_at(&*regs, req.offset)?.access, "ro" | "rc") {
+        return Err(StatusCode::FORBIDDEN);
+    }
     regs.write_register(req.offset, req.value)
         .map(|()| StatusCode::OK)
         .ok_or(StatusCode::BAD_REQUEST)
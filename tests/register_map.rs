@@ -0,0 +1,272 @@
+//! Runtime behaviour of `register_map!` accessors, on the emulator.
+
+use std::sync::{Arc, Mutex};
+
+use ddevmem::backend::{Backend, Emulator, Word};
+use ddevmem::{register_map, DevMem};
+
+register_map! {
+    pub unsafe map Regs (u32) {
+        0x00 => rw trim: u32 {
+            nib:  0..=3 as i8,
+            byte: 8..=15 as i8,
+            top:  16..=31 as i16
+        },
+        0x04 => rw buck: u32 {
+            vout: 0..=7 scale 12.5 offset 600 unit "mV",
+            gain: 8..=19 as fixed(Q4.8),
+            ugain: 20..=27 as fixed(UQ4.4)
+        },
+        0x08 => rw ctrl: u32 {
+            enable: 0 as bool,
+            w1c overrun: 8 as bool,
+            w1c underrun: 9 as bool
+        },
+        0x0C => wo cmd: u32 = 0x0000_0500 {
+            op:  0..=3,
+            arg: 8..=15
+        }
+    }
+}
+
+/// Emulator that logs every bus access.
+struct Recorder {
+    mem: Emulator,
+    log: Mutex<Vec<Access>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Read(usize),
+    Write(usize, u64),
+}
+
+impl Recorder {
+    fn new(size: usize) -> Self {
+        Self {
+            mem: Emulator::new(size),
+            log: Mutex::default(),
+        }
+    }
+}
+
+impl Backend for Recorder {
+    fn len(&self) -> usize {
+        self.mem.len()
+    }
+
+    unsafe fn read<W: Word>(&self, offset: usize) -> W {
+        self.log.lock().unwrap().push(Access::Read(offset));
+        self.mem.read(offset)
+    }
+
+    unsafe fn write<W: Word>(&self, offset: usize, value: W) {
+        self.log
+            .lock()
+            .unwrap()
+            .push(Access::Write(offset, value.to_u64()));
+        self.mem.write(offset, value);
+    }
+
+    unsafe fn preset<W: Word>(&self, offset: usize, value: W) {
+        self.mem.preset(offset, value);
+    }
+}
+
+fn emulated() -> (Arc<DevMem<Emulator>>, Regs<Emulator>) {
+    let devmem = Arc::new(unsafe { DevMem::new(0x4000_0000, Some(0x10)).unwrap() });
+    let regs = unsafe { Regs::new(devmem.clone()).unwrap() };
+    (devmem, regs)
+}
+
+fn recorded() -> (Arc<DevMem<Recorder>>, Regs<Recorder>) {
+    let devmem = Arc::new(DevMem::from_backend(0x4000_0000, Recorder::new(0x10)));
+    let regs = unsafe { Regs::new(devmem.clone()).unwrap() };
+    (devmem, regs)
+}
+
+/// Sets a register behind the map's back, as the hardware would.
+fn poke(devmem: &DevMem<Recorder>, offset: usize, value: u32) {
+    unsafe { devmem.backend().mem.write(offset, value) }
+}
+
+/// Takes the accesses logged so far.
+fn drain(devmem: &DevMem<Recorder>) -> Vec<Access> {
+    std::mem::take(&mut *devmem.backend().log.lock().unwrap())
+}
+
+#[test]
+fn signed_fields_sign_extend_from_their_top_bit() {
+    let (devmem, mut regs) = emulated();
+
+    devmem.write(0x00, 0x7FFF_8007u32).unwrap();
+    assert_eq!(
+        (regs.trim_nib(), regs.trim_byte(), regs.trim_top()),
+        (7, -128, 0x7FFF)
+    );
+    devmem.write(0x00, 0x8000_7F08u32).unwrap();
+    assert_eq!(
+        (regs.trim_nib(), regs.trim_byte(), regs.trim_top()),
+        (-8, 127, i16::MIN)
+    );
+    // Bits of the neighbouring fields never leak into the sign.
+    devmem.write(0x00, 0xFFFF_FF0Fu32).unwrap();
+    assert_eq!(
+        (regs.trim_nib(), regs.trim_byte(), regs.trim_top()),
+        (-1, -1, -1)
+    );
+    devmem.write(0x00, 0xFFFF_00F0u32).unwrap();
+    assert_eq!((regs.trim_nib(), regs.trim_byte()), (0, 0));
+
+    // Setters write the two's-complement bits of the field alone.
+    devmem.write(0x00, 0u32).unwrap();
+    regs.set_trim_nib(-8);
+    assert_eq!(regs.trim(), 0x0000_0008);
+    regs.set_trim_top(-1);
+    assert_eq!(regs.trim(), 0xFFFF_0008);
+    regs.set_trim_byte(i8::MIN);
+    assert_eq!(regs.trim(), 0xFFFF_8008);
+    regs.set_trim_top(i16::MAX);
+    assert_eq!(regs.trim(), 0x7FFF_8008);
+
+    let value = regs.read_trim();
+    assert_eq!(
+        (value.nib(), value.byte(), value.top()),
+        (-8, -128, i16::MAX)
+    );
+}
+
+#[test]
+fn try_setters_reject_values_outside_the_field() {
+    let (_devmem, mut regs) = emulated();
+    regs.set_trim(0x1234_5600);
+
+    for rejected in [8, 9, i8::MAX, -9, i8::MIN] {
+        assert_eq!(regs.try_set_trim_nib(rejected), Err(rejected));
+        assert_eq!(regs.read_trim().try_with_nib(rejected), Err(rejected));
+    }
+    assert_eq!(regs.trim(), 0x1234_5600, "a rejected value is not written");
+
+    for accepted in [-8, -1, 0, 7] {
+        assert_eq!(regs.try_set_trim_nib(accepted), Ok(()));
+        assert_eq!(regs.trim_nib(), accepted);
+    }
+    assert_eq!(regs.trim() & !0xF, 0x1234_5600);
+
+    // A field as wide as its type takes every value.
+    for value in [i8::MIN, -1, 0, i8::MAX] {
+        assert_eq!(regs.try_set_trim_byte(value), Ok(()));
+        assert_eq!(regs.trim_byte(), value);
+    }
+}
+
+#[test]
+fn scaled_fields_round_trip_and_round_to_the_nearest_step() {
+    let (_devmem, mut regs) = emulated();
+
+    for raw in 0..=255u32 {
+        regs.set_buck_vout(raw);
+        let mv = regs.buck_vout_f64();
+        assert_eq!(mv, 600.0 + 12.5 * f64::from(raw));
+        regs.set_buck_vout(0);
+        regs.set_buck_vout_f64(mv);
+        assert_eq!(regs.buck_vout(), raw);
+        regs.set_buck_vout_f32(mv as f32);
+        assert_eq!(regs.buck_vout(), raw);
+    }
+
+    for (mv, raw) in [
+        (1806.2, 96),
+        (1806.3, 97),
+        (593.0, 0),
+        (5000.0, 255),
+        (f64::NAN, 0),
+    ] {
+        regs.set_buck_vout_f64(mv);
+        assert_eq!(regs.buck_vout(), raw, "{mv} mV");
+    }
+
+    // Q4.8: -8.0 ..= 8.0 - 1/256, in steps of 1/256.
+    for (value, raw) in [
+        (-8.0, 0x800),
+        (8.0 - 1.0 / 256.0, 0x7FF),
+        (-1.5, 0xE80),
+        (0.0019, 0x000),
+        (0.0021, 0x001),
+        (-0.0021, 0xFFF),
+        (100.0, 0x7FF),
+        (-100.0, 0x800),
+    ] {
+        regs.set_buck_gain_f64(value);
+        assert_eq!(regs.buck_gain(), raw, "{value}");
+    }
+    regs.set_buck_gain(0xE80);
+    assert_eq!(regs.read_buck().gain_f32(), -1.5);
+
+    // UQ4.4 saturates at zero instead of wrapping.
+    for (value, raw) in [(-1.0, 0x00), (15.9375, 0xFF), (16.0, 0xFF), (2.53, 0x28)] {
+        regs.set_buck_ugain_f32(value);
+        assert_eq!(regs.buck_ugain(), raw, "{value}");
+    }
+    assert_eq!(regs.buck_gain(), 0xE80, "neighbouring fields are kept");
+}
+
+#[test]
+fn w1c_fields_are_written_as_zero_by_other_updates() {
+    let (devmem, mut regs) = recorded();
+    poke(&devmem, 0x08, 0x0000_0300); // both flags pending
+
+    regs.modify_ctrl(|value| {
+        assert_eq!(value & 0x300, 0, "pending flags reach the closure as 0");
+        value | 1
+    });
+    assert_eq!(
+        drain(&devmem),
+        [Access::Read(0x08), Access::Write(0x08, 0x001)]
+    );
+
+    poke(&devmem, 0x08, 0x0000_0301);
+    regs.modify_ctrl_fields(|r, w| {
+        assert!(r.enable() && r.overrun() && r.underrun());
+        assert!(w.enable() && !w.overrun() && !w.underrun());
+        w.with_enable(false)
+    });
+    regs.set_ctrl_enable(true);
+    let writes: Vec<_> = drain(&devmem)
+        .into_iter()
+        .filter(|access| matches!(access, Access::Write(..)))
+        .collect();
+    assert_eq!(
+        writes,
+        [Access::Write(0x08, 0x000), Access::Write(0x08, 0x001)]
+    );
+
+    // Clearing one flag leaves the other pending.
+    poke(&devmem, 0x08, 0x0000_0301);
+    regs.set_ctrl_overrun(true);
+    assert_eq!(drain(&devmem).last(), Some(&Access::Write(0x08, 0x101)));
+}
+
+#[test]
+fn wo_setters_never_read_the_register() {
+    let (devmem, mut regs) = recorded();
+    // Whatever the hardware returns for a write-only register is ignored:
+    // the fields start from the declared reset value.
+    poke(&devmem, 0x0C, 0xDEAD_BEEF);
+
+    regs.set_cmd_op(3);
+    regs.set_cmd_arg(0x12);
+    regs.write_cmd_fields(|value| value.with_op(1));
+    regs.set_cmd(0xFF);
+    regs.set_cmd_op(2);
+    assert_eq!(
+        drain(&devmem),
+        [
+            Access::Write(0x0C, 0x0503),
+            Access::Write(0x0C, 0x1203),
+            Access::Write(0x0C, 0x0501),
+            Access::Write(0x0C, 0x00FF),
+            Access::Write(0x0C, 0x00F2),
+        ]
+    );
+}
@@ -0,0 +1,7 @@
+//! Compile errors of `register_map!`, checked against `tests/ui/*.stderr`.
+//! Regenerate the expectations with `TRYBUILD=overwrite cargo test --test ui`.
+
+#[test]
+fn register_map_diagnostics() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
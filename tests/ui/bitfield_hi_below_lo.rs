@@ -0,0 +1,11 @@
+use ddevmem::register_map;
+
+register_map! {
+    pub unsafe map Uart (u32) {
+        0x00 => rw control: u32 {
+            baud: 7..=4
+        }
+    }
+}
+
+fn main() {}
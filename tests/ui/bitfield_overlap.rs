@@ -0,0 +1,12 @@
+use ddevmem::register_map;
+
+register_map! {
+    pub unsafe map Uart (u32) {
+        0x00 => rw ctrl: u32 {
+            baud: 0..=7,
+            parity: 6..=8
+        }
+    }
+}
+
+fn main() {}
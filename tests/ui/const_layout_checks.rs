@@ -0,0 +1,22 @@
+// Positions given as constants are checked by `const` assertions, which
+// must still point at the offending entry.
+use ddevmem::register_map;
+
+const FIFO: usize = 0x00;
+const LO: u32 = 4;
+const HI: u32 = 3;
+
+register_map! {
+    pub unsafe map Uart (u32) {
+        FIFO => rw fifo: [u32; 4],
+        0x0C => rw ctrl: u32 {
+            baud: (LO)..=7,
+            parity: 6..=8,
+            empty: (LO)..=(HI),
+            trim: 10..=(HI + 16) as i8,
+            gain: 20..=(HI + 27) as fixed(Q4.8)
+        }
+    }
+}
+
+fn main() {}
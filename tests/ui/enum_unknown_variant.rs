@@ -0,0 +1,11 @@
+use ddevmem::register_map;
+
+register_map! {
+    pub unsafe map Motor (u32) {
+        0x00 => rw ctrl: u32 {
+            dir: 0..=1 as enum(unknown) Dir { Fwd = 0, Rev = 1, Unknown = 2 }
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use ddevmem::register_map;
+
+register_map! {
+    pub unsafe map Pmic (u32) {
+        0x00 => rw buck: u32 {
+            gain: 8..=18 as fixed(Q4.8)
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use ddevmem::register_map;
+
+register_map! {
+    pub unsafe map Irq (u32) {
+        0x00 => ro status: u32 {
+            ready: 0 as bool,
+            w1c overrun: 8 as bool
+        }
+    }
+}
+
+fn main() {}
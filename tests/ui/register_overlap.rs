@@ -0,0 +1,10 @@
+use ddevmem::register_map;
+
+register_map! {
+    pub unsafe map Uart (u32) {
+        0x00 => rw fifo: [u32; 4],
+        0x08 => rw ctrl: u32
+    }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use ddevmem::register_map;
+
+register_map! {
+    pub unsafe map Uart (u16) {
+        0x00 => rw control: u16 = 0x1_0000
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use ddevmem::register_map;
+
+register_map! {
+    pub unsafe map Sensor (u32) {
+        0x00 => rw trim: u32 {
+            bias: 0..=11 as i8
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,124 @@
+//! Raw register access through the web UI's `RegisterMapInfo` and API.
+#![cfg(feature = "web")]
+
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    sync::Arc,
+    thread,
+};
+
+use ddevmem::{register_map, web::RegisterMapInfo, web::WebUi, DevMem};
+use tokio::{net::TcpListener, sync::Mutex};
+
+register_map! {
+    pub unsafe map Dma (u32) {
+        0x00 => wo cmd: u32 {
+            start: 0 as bool,
+            channel: 4..=7
+        },
+        0x04 => ro status: u32,
+        0x08 => rc events: u32,
+        0x0C => rw ctrl: u32 {
+            enable: 0 as bool,
+            rc overrun: 8 as bool
+        },
+        0x10 => wo queue: [u32; 2] {
+            count: 0..=7
+        }
+    }
+}
+
+fn dma() -> (Arc<DevMem>, Dma) {
+    let devmem = Arc::new(unsafe { DevMem::new(0x4000_0000, Some(0x18)).unwrap() });
+    let dma = unsafe { Dma::new(devmem.clone()).unwrap() };
+    (devmem, dma)
+}
+
+#[test]
+fn raw_writes_update_write_only_shadows() {
+    let (devmem, mut dma) = dma();
+
+    dma.write_register(0x00, 0x50).unwrap();
+    dma.set_cmd_start(true);
+    assert_eq!(devmem.read::<u32>(0x00), Some(0x51));
+    assert_eq!(dma.shadow_register(0x00), Some(0x51));
+
+    dma.write_register(0x14, 0xAB00).unwrap();
+    dma.set_queue_count(1, 0x12);
+    assert_eq!(devmem.read::<u32>(0x14), Some(0xAB12));
+    dma.set_queue_count(0, 0x34);
+    assert_eq!(devmem.read::<u32>(0x10), Some(0x34));
+    assert_eq!(dma.shadow_register(0x14), Some(0xAB12));
+    assert_eq!(dma.shadow_register(0x0C), None);
+
+    // Failed writes leave the shadows alone.
+    assert_eq!(dma.write_register(0x18, 0xFF), None);
+    dma.set_queue_count(1, 0x56);
+    assert_eq!(devmem.read::<u32>(0x14), Some(0xAB56));
+}
+
+/// Sends one request to the server at `addr`; returns the status code and
+/// body.
+fn post(addr: SocketAddr, path: &str, body: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let request = format!(
+        "POST /api/dma{path} HTTP/1.1\r\nHost: test\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response[9..12].parse().unwrap();
+    let body = response.split("\r\n\r\n").nth(1).unwrap_or_default();
+    (status, body.to_owned())
+}
+
+/// Serves `app` on a background thread and returns its address.
+fn serve(app: axum::Router) -> SocketAddr {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let listener = runtime.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || runtime.block_on(async { axum::serve(listener, app).await.unwrap() }));
+    addr
+}
+
+#[test]
+fn api_honours_access_kinds() {
+    let (devmem, dma) = dma();
+    devmem.write(0x00, 0xDEADu32).unwrap();
+    devmem.write(0x04, 0x1234u32).unwrap();
+    devmem.write(0x08, 0x5u32).unwrap();
+    devmem.write(0x0C, 0x101u32).unwrap();
+
+    let app = WebUi::new().add("dma", Arc::new(Mutex::new(dma))).build();
+    let addr = serve(app);
+
+    // Reads: never `wo`, and read-clear registers only on request.
+    assert_eq!(
+        post(addr, "/read", r#"{"offset":4}"#),
+        (200, r#"{"value":4660}"#.into())
+    );
+    assert_eq!(post(addr, "/read", r#"{"offset":0}"#).0, 403);
+    assert_eq!(post(addr, "/read", r#"{"offset":8}"#).0, 403);
+    assert_eq!(post(addr, "/read", r#"{"offset":12}"#).0, 403);
+    assert_eq!(devmem.read::<u32>(0x08), Some(0x5));
+    let read = post(addr, "/read", r#"{"offset":8,"side_effects":true}"#);
+    assert_eq!(read, (200, r#"{"value":5}"#.into()));
+    assert_eq!(post(addr, "/read", r#"{"offset":2}"#).0, 404);
+
+    // Writes: never `ro` or `rc`.
+    assert_eq!(post(addr, "/write", r#"{"offset":4,"value":1}"#).0, 403);
+    assert_eq!(post(addr, "/write", r#"{"offset":8,"value":1}"#).0, 403);
+    assert_eq!(devmem.read::<u32>(0x04), Some(0x1234));
+    assert_eq!(post(addr, "/write", r#"{"offset":0,"value":48}"#).0, 200);
+
+    // `wo` registers are read from the shadow, not the bus.
+    let shadow = post(addr, "/read", r#"{"offset":0,"shadow":true}"#);
+    assert_eq!(shadow, (200, r#"{"value":48}"#.into()));
+    assert_eq!(post(addr, "/read", r#"{"offset":4,"shadow":true}"#).0, 400);
+}